@@ -42,15 +42,15 @@ fn main() -> Result<(), Error> {
     let query = QueryExecution::parse_query(&format!(
         "FROM {name} WHERE sport = \"Basketball\" ORDER BY score DESC"
     ))?;
-    let players = engine.query(query);
+    let players = engine.query(query)?.items;
 
     println!("Basketball players sorted by score:\n{:?}\n", players);
 
     let players = engine.query(
         QueryExecution::parse_query(&format!(
-            "FROM {name} WHERE birth_date >= \"1980-01-01\" && birth_date <= \"1989-12-31\" ORDER BY name ASC"
+            "FROM {name} WHERE birth_date BETWEEN \"1980-01-01\" AND \"1989-12-31\" ORDER BY name ASC"
         ))?
-    );
+    )?.items;
 
     println!("Players born in the 80s:\n{:?}\n", players);
 
@@ -74,7 +74,7 @@ fn main() -> Result<(), Error> {
         1,
     )?));
 
-    let players = engine.query(query)?;
+    let players = engine.query(query)?.items;
 
     println!(
         "Basketball players sorted by score after switching sports in 2023:\n{:?}\n",
@@ -98,7 +98,7 @@ fn main() -> Result<(), Error> {
             Date::from_calendar_date(2024, Month::January, 1)?,
         ));
 
-    let players = engine.query(query)?;
+    let players = engine.query(query)?.items;
 
     println!(
         "Players sorted by score after decreasing their score by 1:\n{:?}\n",
@@ -107,9 +107,11 @@ fn main() -> Result<(), Error> {
 
     engine.remove(name, &david_id)?;
 
-    let players = engine.query(QueryExecution::parse_query(&format!(
-        "FROM {name} WHERE sport = \"Basketball\"",
-    ))?)?;
+    let players = engine
+        .query(QueryExecution::parse_query(&format!(
+            "FROM {name} WHERE sport = \"Basketball\"",
+        ))?)?
+        .items;
 
     println!(
         "Players playing basketball after deletion:\n{:?}\n",
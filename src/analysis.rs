@@ -0,0 +1,348 @@
+use std::ops::Range;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Languages supported for removing stop words and stemming.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    English,
+    Spanish,
+    French,
+    German,
+    Italian,
+    Portuguese,
+}
+
+impl Language {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            Language::English => Algorithm::English,
+            Language::Spanish => Algorithm::Spanish,
+            Language::French => Algorithm::French,
+            Language::German => Algorithm::German,
+            Language::Italian => Algorithm::Italian,
+            Language::Portuguese => Algorithm::Portuguese,
+        }
+    }
+
+    fn stop_words(&self) -> &'static [&'static str] {
+        match self {
+            Language::English => ENGLISH_STOP_WORDS,
+            Language::Spanish => SPANISH_STOP_WORDS,
+            Language::French => FRENCH_STOP_WORDS,
+            Language::German => GERMAN_STOP_WORDS,
+            Language::Italian => ITALIAN_STOP_WORDS,
+            Language::Portuguese => PORTUGUESE_STOP_WORDS,
+        }
+    }
+}
+
+/// An analyzer splits a text into the terms stored in a term index. The same analyzer
+/// is used when indexing values and when searching them, so that both produce the
+/// same terms.
+///
+/// Texts are split into words following the Unicode word boundaries, and every word is
+/// case folded. Optionally, stop words are removed, words are stemmed and accents are
+/// folded, in this order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Analyzer {
+    /// Language of the texts, required for removing stop words and stemming.
+    pub language: Option<Language>,
+    /// Remove accents and other diacritics from the words (e.g. "é" becomes "e").
+    pub fold_accents: bool,
+    /// Remove the most common words of the language (e.g. "the" or "and").
+    pub stop_words: bool,
+    /// Reduce the words to their stem (e.g. "searching" becomes "search").
+    pub stemming: bool,
+}
+
+impl Analyzer {
+    /// Split a text into its terms, in the order they appear in the text.
+    pub(crate) fn analyze(&self, text: &str) -> Vec<String> {
+        self.analyze_with_offsets(text)
+            .into_iter()
+            .map(|(term, _)| term)
+            .collect()
+    }
+
+    /// Split a text into its terms as `analyze`, together with the character offsets of
+    /// the words they were built from in the original text.
+    pub(crate) fn analyze_with_offsets(&self, text: &str) -> Vec<(String, Range<usize>)> {
+        let stemmer = self
+            .language
+            .filter(|_| self.stemming)
+            .map(|language| Stemmer::create(language.algorithm()));
+
+        // Word boundaries are given in bytes, which are converted to characters
+        let mut char_offset = 0;
+        let mut byte_offset = 0;
+
+        text.unicode_word_indices()
+            .filter_map(|(start, word)| {
+                char_offset += text[byte_offset..start].chars().count();
+                byte_offset = start + word.len();
+
+                let chars = char_offset..char_offset + word.chars().count();
+                char_offset = chars.end;
+
+                let word = fold_case(word);
+                if word.is_empty() || self.is_stop_word(&word) {
+                    return None;
+                }
+
+                let word = match &stemmer {
+                    Some(stemmer) => stemmer.stem(&word).into_owned(),
+                    None => word,
+                };
+
+                if self.fold_accents {
+                    Some((fold_accents(&word), chars))
+                } else {
+                    Some((word, chars))
+                }
+            })
+            .collect()
+    }
+
+    /// Normalize the last word of a prefix, as typed while searching, so that it can be
+    /// compared with the beginning of the terms. Stop words and stemming don't apply,
+    /// since the word is likely incomplete.
+    pub(crate) fn analyze_prefix(&self, prefix: &str) -> Option<String> {
+        let word = fold_case(prefix.unicode_words().next_back()?);
+        let word = if self.fold_accents {
+            fold_accents(&word)
+        } else {
+            word
+        };
+
+        (!word.is_empty()).then_some(word)
+    }
+
+    /// Normalize the literal text of a pattern as the words of the terms are, so that it can
+    /// be compared with them. Only letters and numbers are folded, other characters are kept,
+    /// and stemming doesn't apply, since the text may be part of a word.
+    pub(crate) fn normalize_literal(&self, text: &str) -> String {
+        let mut normalized = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                normalized.push_str(&fold_case(c.encode_utf8(&mut [0; 4])));
+            } else {
+                normalized.push(c);
+            }
+        }
+
+        if self.fold_accents {
+            fold_accents(&normalized)
+        } else {
+            normalized
+        }
+    }
+
+    fn is_stop_word(&self, word: &str) -> bool {
+        match self.language {
+            Some(language) if self.stop_words => language.stop_words().contains(&word),
+            _ => false,
+        }
+    }
+}
+
+/// A set of synonyms searched together with the terms of a term index. Searching any of
+/// the `terms` also searches the other ones, unless `synonyms` are given, in which case
+/// searching any of the `terms` also searches the `synonyms`, but not the other way around.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SynonymSet {
+    pub terms: Vec<String>,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+}
+
+impl SynonymSet {
+    /// Create a set of interchangeable terms, e.g. "football" and "soccer".
+    pub fn equivalent(terms: &[&str]) -> Self {
+        SynonymSet {
+            terms: terms.iter().map(|term| term.to_string()).collect(),
+            synonyms: Vec::new(),
+        }
+    }
+
+    /// Create a set of terms that are expanded into their synonyms, e.g. "nba" into
+    /// "basketball".
+    pub fn one_way(terms: &[&str], synonyms: &[&str]) -> Self {
+        SynonymSet {
+            terms: terms.iter().map(|term| term.to_string()).collect(),
+            synonyms: synonyms.iter().map(|term| term.to_string()).collect(),
+        }
+    }
+}
+
+/// Fold the case of a word, so that words only differing in case are equal. Compatibility
+/// characters (e.g. ligatures or full-width letters) are decomposed first, and characters
+/// other than letters and numbers are removed.
+fn fold_case(word: &str) -> String {
+    let mut folded = String::with_capacity(word.len());
+
+    for c in word.nfkc().filter(|c| c.is_alphanumeric()) {
+        // Lower case mappings differing from the full case folding
+        match c {
+            'ß' | 'ẞ' => folded.push_str("ss"),
+            'ς' => folded.push('σ'),
+            c => folded.extend(c.to_lowercase()),
+        }
+    }
+
+    folded
+}
+
+/// Remove the accents and other diacritics of a word by decomposing its characters and
+/// dropping the combining marks.
+fn fold_accents(word: &str) -> String {
+    word.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .nfc()
+        .collect()
+}
+
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "has", "have", "he",
+    "her", "his", "i", "if", "in", "into", "is", "it", "its", "of", "on", "or", "our", "she", "so",
+    "such", "that", "the", "their", "then", "there", "these", "they", "this", "to", "was", "we",
+    "were", "will", "with", "you", "your",
+];
+
+const SPANISH_STOP_WORDS: &[&str] = &[
+    "a", "al", "como", "con", "de", "del", "el", "en", "es", "esta", "este", "la", "las", "le",
+    "les", "lo", "los", "me", "mi", "no", "o", "para", "pero", "por", "que", "se", "si", "sin",
+    "su", "sus", "te", "tu", "u", "un", "una", "uno", "y", "ya",
+];
+
+const FRENCH_STOP_WORDS: &[&str] = &[
+    "au", "aux", "avec", "ce", "ces", "dans", "de", "des", "du", "elle", "en", "est", "et", "il",
+    "je", "la", "le", "les", "leur", "lui", "ma", "mais", "me", "mon", "ne", "nous", "on", "ou",
+    "par", "pas", "pour", "qu", "que", "qui", "sa", "se", "son", "sur", "ta", "te", "tu", "un",
+    "une", "vous",
+];
+
+const GERMAN_STOP_WORDS: &[&str] = &[
+    "aber", "als", "am", "an", "auch", "auf", "aus", "bei", "das", "dass", "dem", "den", "der",
+    "des", "die", "ein", "eine", "einem", "einen", "einer", "er", "es", "für", "hat", "ich", "im",
+    "in", "ist", "mit", "nicht", "noch", "oder", "sich", "sie", "sind", "so", "und", "von", "war",
+    "wie", "wir", "zu", "zum", "zur",
+];
+
+const ITALIAN_STOP_WORDS: &[&str] = &[
+    "a", "al", "alla", "che", "chi", "ci", "con", "da", "dal", "dei", "del", "della", "di", "e",
+    "gli", "i", "il", "in", "la", "le", "lo", "ma", "mi", "ne", "nel", "nella", "non", "o", "per",
+    "più", "se", "si", "su", "sua", "suo", "un", "una", "uno",
+];
+
+const PORTUGUESE_STOP_WORDS: &[&str] = &[
+    "a", "ao", "as", "com", "como", "da", "das", "de", "do", "dos", "e", "ela", "ele", "em", "na",
+    "nas", "no", "nos", "não", "o", "os", "ou", "para", "pela", "pelo", "por", "que", "se", "sem",
+    "seu", "sua", "um", "uma",
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Analyzer, Language};
+
+    #[test]
+    fn analyzes_unicode_words() {
+        // given
+        let analyzer = Analyzer::default();
+
+        // when
+        let terms = analyzer.analyze("ÉCOLE Straße, don't stop! ﬁnal 東京タワー");
+
+        // then
+        assert_eq!(
+            terms,
+            vec![
+                "école",
+                "strasse",
+                "dont",
+                "stop",
+                "final",
+                "東",
+                "京",
+                "タワー"
+            ]
+        );
+    }
+
+    #[test]
+    fn analyzes_with_accent_folding() {
+        // given
+        let analyzer = Analyzer {
+            fold_accents: true,
+            ..Analyzer::default()
+        };
+
+        // when
+        let terms = analyzer.analyze("Crème brûlée à São Paulo");
+
+        // then
+        assert_eq!(terms, vec!["creme", "brulee", "a", "sao", "paulo"]);
+    }
+
+    #[test]
+    fn analyzes_with_stop_words_and_stemming() {
+        // given
+        let analyzer = Analyzer {
+            language: Some(Language::English),
+            stop_words: true,
+            stemming: true,
+            ..Analyzer::default()
+        };
+
+        // when
+        let terms = analyzer.analyze("The engines are searching for the Deltas");
+
+        // then
+        assert_eq!(terms, vec!["engin", "search", "delta"]);
+    }
+
+    #[test]
+    fn analyzes_with_offsets() {
+        // given
+        let analyzer = Analyzer {
+            language: Some(Language::English),
+            stop_words: true,
+            ..Analyzer::default()
+        };
+
+        // when
+        let terms = analyzer.analyze_with_offsets("Über the «naïve» engine");
+
+        // then
+        assert_eq!(
+            terms,
+            vec![
+                ("über".to_string(), 0..4),
+                ("naïve".to_string(), 10..15),
+                ("engine".to_string(), 17..23)
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_stop_words_and_stemming_without_language() {
+        // given
+        let analyzer = Analyzer {
+            stop_words: true,
+            stemming: true,
+            ..Analyzer::default()
+        };
+
+        // when
+        let terms = analyzer.analyze("The searching");
+
+        // then
+        assert_eq!(terms, vec!["the", "searching"]);
+    }
+}
@@ -105,6 +105,15 @@ impl FieldValue {
         }
     }
 
+    /// The numeric value of either an integer or a decimal.
+    pub(crate) fn as_number(&self) -> Option<OrderedFloat<f64>> {
+        match self {
+            FieldValue::Integer(value) => Some(OrderedFloat(*value as f64)),
+            FieldValue::Decimal(value) => Some(*value),
+            _ => None,
+        }
+    }
+
     pub(crate) fn get_decimal(self) -> Option<OrderedFloat<f64>> {
         if let FieldValue::Decimal(value) = self {
             Some(value)
@@ -234,6 +243,7 @@ pub enum FieldValueExternal {
     Decimal(f64),
     Map(HashMap<String, FieldValueExternal>),
     Seq(Vec<FieldValueExternal>),
+    Null,
 }
 
 impl FieldValueExternal {
@@ -281,12 +291,43 @@ impl FieldValueExternal {
                     values.push((key, FieldValue::Array(array_values)));
                 }
             }
+            // Null values are skipped, so that the indices track them as missing
+            FieldValueExternal::Null => {}
         }
 
         values
     }
 }
 
+impl From<&FieldValue> for FieldValueExternal {
+    fn from(value: &FieldValue) -> Self {
+        as_external(value)
+    }
+}
+
+impl TryFrom<FieldValueExternal> for FieldValue {
+    type Error = &'static str;
+
+    /// Read a single value, where maps and nulls are not supported as they are only
+    /// meaningful when flattening data items.
+    fn try_from(value: FieldValueExternal) -> Result<Self, Self::Error> {
+        match value {
+            FieldValueExternal::Bool(value) => Ok(FieldValue::Bool(value)),
+            FieldValueExternal::Integer(value) => Ok(FieldValue::Integer(value)),
+            FieldValueExternal::String(value) => Ok(FieldValue::String(value)),
+            FieldValueExternal::Decimal(value) => Ok(FieldValue::Decimal(OrderedFloat(value))),
+            FieldValueExternal::Seq(values) => values
+                .into_iter()
+                .map(FieldValue::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map(FieldValue::Array),
+            FieldValueExternal::Map(_) | FieldValueExternal::Null => {
+                Err("expected a boolean, number, string or array value")
+            }
+        }
+    }
+}
+
 fn as_external(field: &FieldValue) -> FieldValueExternal {
     match field {
         FieldValue::Bool(value) => FieldValueExternal::Bool(*value),
@@ -392,6 +433,37 @@ mod tests {
         )
     }
 
+    #[test]
+    fn deserializes_null_as_missing() {
+        // given
+        let input = r#"{
+            "name": "Elephant",
+            "score": null,
+            "family": {
+                "name": null,
+                "size": [null, 3]
+            }
+        }"#;
+
+        // when
+        let data: DataItemFieldsExternal = serde_json::from_str(input).unwrap();
+
+        // then
+        assert_eq!(
+            data,
+            DataItemFieldsExternal::new(BTreeMap::from([
+                (
+                    "name".to_string(),
+                    FieldValue::String("Elephant".to_string())
+                ),
+                (
+                    "family.size".to_string(),
+                    FieldValue::array([FieldValue::int(3)])
+                )
+            ]))
+        )
+    }
+
     #[test]
     fn serializes() {
         // given
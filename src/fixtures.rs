@@ -5,6 +5,7 @@ use std::sync::Mutex;
 
 use serde::{Deserialize, Serialize};
 
+use crate::analysis::Analyzer;
 use crate::data::FieldValue;
 use crate::index::{StringTypeDescriptor, TypeDescriptor};
 use crate::query::DeltaChange;
@@ -39,6 +40,27 @@ pub fn david() -> DataItem {
     Player::new(4, "David", Sport::Basketball, "1974-10-01", false).as_item()
 }
 
+/// Clone a player adding a string field the players don't have, e.g. to index it.
+pub fn with_string_field(item: &DataItem, field: &str, value: &str) -> DataItem {
+    let mut item = item.clone();
+    item.fields
+        .insert(field.to_string(), FieldValue::str(value));
+    item
+}
+
+/// Describe a string index for a field, optionally with a term index using the default
+/// analyzer and with a trigram index.
+pub fn string_index(field: &str, term: bool, trigram: bool) -> CreateFieldIndex {
+    CreateFieldIndex {
+        name: field.to_string(),
+        descriptor: TypeDescriptor::String(StringTypeDescriptor {
+            term,
+            analyzer: Analyzer::default(),
+            trigram,
+        }),
+    }
+}
+
 pub(crate) struct TestPlayerRunner {
     pub(crate) name: String,
     pub(crate) engine: Engine,
@@ -132,10 +154,7 @@ fn carry_players(items: Vec<DataItem>, storage: &EntityStorage) {
 
     storage
         .create_indices(vec![
-            CreateFieldIndex {
-                name: "name".to_string(),
-                descriptor: TypeDescriptor::String(StringTypeDescriptor { term: true }),
-            },
+            string_index("name", true, false),
             CreateFieldIndex {
                 name: "sport".to_string(),
                 descriptor: TypeDescriptor::Enum(HashSet::from_iter([
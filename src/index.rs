@@ -1,13 +1,17 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
 use std::iter::FromIterator;
-use std::ops::Bound;
+use std::ops::{Bound, Range};
 use std::panic;
+use std::sync::OnceLock;
 
+use crate::analysis::{Analyzer, SynonymSet};
 use crate::data::{date_to_timestamp, parse_date, timestamp_to_date, FieldValue};
-use crate::query::{FilterName, FilterOperation, SortDirection};
+use crate::query::{AggregateFunction, FilterName, FilterOperation, NullsPlacement, SortDirection};
 use indexmap::IndexSet;
 use ordered_float::OrderedFloat;
+use regex::Regex;
 use roaring::{MultiOps, RoaringBitmap};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -25,9 +29,13 @@ pub enum TypeDescriptor {
 #[derive(Clone, Debug)]
 pub struct StringTypeDescriptor {
     pub term: bool,
+    /// Analyzer splitting the values into terms, if the term index is enabled.
+    pub analyzer: Analyzer,
+    /// Index the trigrams of the values to search for substrings.
+    pub trigram: bool,
 }
 
-trait FilterableIndex {
+trait FilterableIndex: ValueIndex {
     fn filter(&self, op: &FilterOperation) -> Result<RoaringBitmap, FilterError> {
         match op {
             FilterOperation::Eq(value) => self.equal(value),
@@ -48,11 +56,32 @@ trait FilterableIndex {
             }
             FilterOperation::Contains(value) => self.contains(value),
             FilterOperation::Matches(value) => self.matches(value),
+            FilterOperation::In(values) => self.in_values(values),
+            FilterOperation::StartsWith(value) => self.starts_with(value),
+            FilterOperation::Substring(value) => self.substring(value),
+            FilterOperation::Fuzzy { value, max_edits } => self.fuzzy(value, *max_edits),
+            FilterOperation::Near {
+                value,
+                slop,
+                ordered,
+            } => self.near(value, *slop, *ordered),
+            FilterOperation::Like(value) => {
+                let pattern = Pattern::from_value(value, FilterName::Like)?;
+                self.matches_pattern(&pattern, FilterName::Like)
+            }
+            FilterOperation::Regex(value) => {
+                let pattern = Pattern::from_value(value, FilterName::Regex)?;
+                self.matches_pattern(&pattern, FilterName::Regex)
+            }
+            FilterOperation::IsNull => Ok(self.sortable().missing.clone()),
+            FilterOperation::Exists => Ok(self.sortable().present()),
         }
     }
 
     fn equal(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError>;
 
+    fn in_values(&self, values: &[FieldValue]) -> Result<RoaringBitmap, FilterError>;
+
     fn between(
         &self,
         first: Bound<&FieldValue>,
@@ -62,6 +91,205 @@ trait FilterableIndex {
     fn contains(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError>;
 
     fn matches(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError>;
+
+    fn starts_with(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError>;
+
+    fn substring(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError>;
+
+    fn fuzzy(&self, value: &FieldValue, max_edits: u32) -> Result<RoaringBitmap, FilterError>;
+
+    fn near(
+        &self,
+        value: &FieldValue,
+        slop: u32,
+        ordered: bool,
+    ) -> Result<RoaringBitmap, FilterError>;
+
+    fn matches_pattern(
+        &self,
+        pattern: &Pattern,
+        filter: FilterName,
+    ) -> Result<RoaringBitmap, FilterError>;
+}
+
+/// A pattern matched against the distinct values of an index (e.g. the keys of a
+/// `StringIndex` or the vocabulary of a `TermIndex`), instead of every item.
+#[derive(Debug)]
+pub(crate) struct Pattern {
+    regex: Regex,
+    /// Literal prefix that every matching value starts with.
+    prefix: String,
+    /// Expression the pattern was built from, to build it again for the terms of a
+    /// term index.
+    expression: PatternExpression,
+}
+
+#[derive(Clone, Debug)]
+enum PatternExpression {
+    Like(Vec<LikeToken>),
+    Regex(String),
+}
+
+#[derive(Clone, Debug)]
+enum LikeToken {
+    Literal(String),
+    /// `%`, matching any sequence of characters.
+    AnyChars,
+    /// `_`, matching a single character.
+    AnyChar,
+}
+
+/// Character escaping the wildcards of a `LIKE` expression, and itself.
+const LIKE_ESCAPE: char = '\\';
+
+impl Pattern {
+    /// Build a pattern from a `LIKE` expression, where `%` matches any sequence of
+    /// characters and `_` matches a single character, unless escaped with a backslash
+    /// (e.g. `100\%`). The whole value must match.
+    pub(crate) fn like(pattern: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut literal = String::new();
+        let mut chars = pattern.chars();
+
+        while let Some(c) = chars.next() {
+            let wildcard = match c {
+                '%' => LikeToken::AnyChars,
+                '_' => LikeToken::AnyChar,
+                LIKE_ESCAPE => {
+                    literal.push(chars.next().unwrap_or(LIKE_ESCAPE));
+                    continue;
+                }
+                c => {
+                    literal.push(c);
+                    continue;
+                }
+            };
+
+            if !literal.is_empty() {
+                tokens.push(LikeToken::Literal(std::mem::take(&mut literal)));
+            }
+            tokens.push(wildcard);
+        }
+
+        if !literal.is_empty() {
+            tokens.push(LikeToken::Literal(literal));
+        }
+
+        Pattern::from_like_tokens(tokens)
+    }
+
+    fn from_like_tokens(tokens: Vec<LikeToken>) -> Self {
+        let mut regex = String::from("(?s)^");
+        for token in &tokens {
+            match token {
+                LikeToken::Literal(literal) => regex.push_str(&regex::escape(literal)),
+                LikeToken::AnyChars => regex.push_str(".*"),
+                LikeToken::AnyChar => regex.push('.'),
+            }
+        }
+        regex.push('$');
+
+        let prefix = match tokens.first() {
+            Some(LikeToken::Literal(literal)) => literal.clone(),
+            _ => String::new(),
+        };
+
+        Pattern {
+            regex: Regex::new(&regex).expect("LIKE pattern must be a valid regular expression"),
+            prefix,
+            expression: PatternExpression::Like(tokens),
+        }
+    }
+
+    /// Build a pattern from a regular expression, matching any value containing a match.
+    pub(crate) fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Pattern {
+            regex: Regex::new(pattern)?,
+            prefix: String::new(),
+            expression: PatternExpression::Regex(pattern.to_string()),
+        })
+    }
+
+    /// Build the pattern matched against the terms analyzed by `analyzer`. The literal text
+    /// of `LIKE` expressions is normalized as the terms are, while regular expressions
+    /// ignore case.
+    fn for_terms(&self, analyzer: &Analyzer) -> Self {
+        match &self.expression {
+            PatternExpression::Like(tokens) => Pattern::from_like_tokens(
+                tokens
+                    .iter()
+                    .map(|token| match token {
+                        LikeToken::Literal(literal) => {
+                            LikeToken::Literal(analyzer.normalize_literal(literal))
+                        }
+                        token => token.clone(),
+                    })
+                    .collect(),
+            ),
+            PatternExpression::Regex(pattern) => Pattern {
+                regex: Regex::new(&format!("(?i){pattern}"))
+                    .expect("case insensitive pattern must be a valid regular expression"),
+                prefix: String::new(),
+                expression: self.expression.clone(),
+            },
+        }
+    }
+
+    fn from_value(value: &FieldValue, filter: FilterName) -> Result<Self, FilterError> {
+        let Some(pattern) = value.as_string() else {
+            return Err(FilterError::InvalidInput {
+                filter,
+                type_name: TypeName::String,
+            });
+        };
+
+        match filter {
+            FilterName::Like => Ok(Pattern::like(pattern)),
+            _ => Pattern::regex(pattern).map_err(|_| FilterError::InvalidPattern {
+                pattern: pattern.to_string(),
+            }),
+        }
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        value.starts_with(&self.prefix) && self.regex.is_match(value)
+    }
+}
+
+/// An index storing its values as sortable keys, which can be converted back into
+/// field values. Allows reading and aggregating values directly from the index.
+trait ValueIndex {
+    type Key: Ord + Clone;
+
+    fn sortable(&self) -> &SortableIndex<Self::Key>;
+
+    fn key_value(&self, key: &Self::Key) -> Option<FieldValue>;
+
+    fn get_value(&self, position: u32) -> Option<FieldValue> {
+        self.sortable()
+            .get_value(position)
+            .and_then(|key| self.key_value(key))
+    }
+
+    fn groups(&self, items: &RoaringBitmap) -> Vec<(FieldValue, RoaringBitmap)> {
+        self.sortable()
+            .groups(items)
+            .into_iter()
+            .filter_map(|(key, group)| self.key_value(key).map(|value| (value, group)))
+            .collect()
+    }
+
+    fn min(&self, items: &RoaringBitmap) -> Option<FieldValue> {
+        self.sortable()
+            .min(items)
+            .and_then(|key| self.key_value(key))
+    }
+
+    fn max(&self, items: &RoaringBitmap) -> Option<FieldValue> {
+        self.sortable()
+            .max(items)
+            .and_then(|key| self.key_value(key))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -78,6 +306,12 @@ impl Index {
         match self {
             Index::String(index) => TypeDescriptor::String(StringTypeDescriptor {
                 term: index.term.is_some(),
+                analyzer: index
+                    .term
+                    .as_ref()
+                    .map(|term| term.analyzer.clone())
+                    .unwrap_or_default(),
+                trigram: index.trigram.is_some(),
             }),
             Index::Numeric(_) => TypeDescriptor::Numeric,
             Index::Date(_) => TypeDescriptor::Date,
@@ -89,12 +323,58 @@ impl Index {
         }
     }
 
+    /// Find the completions of a prefix within the `items`, together with the amount of
+    /// items containing each of them. Only string indices can be completed.
+    pub(crate) fn suggest(
+        &self,
+        prefix: &str,
+        items: &RoaringBitmap,
+    ) -> Result<Vec<(String, u64)>, IndexError> {
+        match self {
+            Index::String(index) => Ok(index.suggest(prefix, items)),
+            _ => Err(IndexError::UnsupportedOperation {
+                operation: "suggest".to_string(),
+            }),
+        }
+    }
+
+    /// Set the synonyms searched together with the terms of the index, replacing any
+    /// previous ones. Only indices with a term index support synonyms.
+    pub(crate) fn set_synonyms(&mut self, sets: &[SynonymSet]) -> Result<(), IndexError> {
+        match self {
+            Index::String(StringIndex {
+                term: Some(term), ..
+            }) => term.set_synonyms(sets),
+            _ => Err(IndexError::UnsupportedOperation {
+                operation: "synonyms".to_string(),
+            }),
+        }
+    }
+
+    /// Whether the index has a term index to search for words.
+    pub(crate) fn has_term(&self) -> bool {
+        matches!(self, Index::String(index) if index.term.is_some())
+    }
+
+    pub(crate) fn type_name(&self) -> TypeName {
+        match self {
+            Index::String(_) => TypeName::String,
+            Index::Numeric(_) => TypeName::Numeric,
+            Index::Date(_) => TypeName::Date,
+            Index::Enum(_) => TypeName::Enum,
+            Index::Bool(_) => TypeName::Bool,
+        }
+    }
+
     pub(crate) fn from_type(value: &TypeDescriptor) -> Self {
         match value {
             TypeDescriptor::String(descriptor) => {
                 let mut index = StringIndex::new();
                 if descriptor.term {
-                    index.set_term(TermIndex::new());
+                    index.set_term(TermIndex::with_analyzer(descriptor.analyzer.clone()));
+                }
+                if descriptor.trigram {
+                    index.set_trigram(TrigramIndex::new());
                 }
 
                 Index::String(index)
@@ -118,13 +398,70 @@ impl Index {
         }
     }
 
-    pub(crate) fn sort(&self, items: &RoaringBitmap, direction: &SortDirection) -> Vec<u32> {
+    /// Add the relevance score of the provided `items` for a given filter operation
+    /// to `scores`. Only term operations on indices with a term index are scored.
+    pub(crate) fn score(
+        &self,
+        op: &FilterOperation,
+        items: &RoaringBitmap,
+        scores: &mut HashMap<u32, f64>,
+    ) {
+        if let Index::String(index) = self {
+            index.score(op, items, scores);
+        }
+    }
+
+    /// Add the score of the `items` similar to the item at `position` to `scores`, by the
+    /// terms they share. Only indices with a term index are scored.
+    pub(crate) fn score_similar(
+        &self,
+        position: u32,
+        items: &RoaringBitmap,
+        scores: &mut HashMap<u32, f64>,
+    ) {
+        if let Index::String(StringIndex {
+            term: Some(term), ..
+        }) = self
+        {
+            term.score_similar(position, items, scores);
+        }
+    }
+
+    /// Find spelling corrections for the searched terms of a filter operation missing from
+    /// the vocabulary. Only term operations on indices with a term index are corrected.
+    pub(crate) fn correct(&self, op: &FilterOperation) -> Vec<Correction> {
+        match self {
+            Index::String(index) => index.correct(op),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Find the character offsets of the words matching a filter operation in the value of
+    /// the item at `position`. Only term operations on indices with a term index match words.
+    pub(crate) fn highlight(
+        &self,
+        op: &FilterOperation,
+        position: u32,
+        value: &str,
+    ) -> Vec<Range<usize>> {
+        match self {
+            Index::String(index) => index.highlight(op, position, value),
+            _ => Vec::new(),
+        }
+    }
+
+    pub(crate) fn sort(
+        &self,
+        items: &RoaringBitmap,
+        direction: &SortDirection,
+        nulls: &NullsPlacement,
+    ) -> Vec<RoaringBitmap> {
         match self {
-            Index::String(index) => index.inner.sort(items, direction),
-            Index::Numeric(index) => index.inner.sort(items, direction),
-            Index::Date(index) => index.inner.sort(items, direction),
-            Index::Enum(index) => index.inner.sort(items, direction),
-            Index::Bool(index) => index.inner.sort(items, direction),
+            Index::String(index) => index.inner.sort(items, direction, nulls),
+            Index::Numeric(index) => index.inner.sort(items, direction, nulls),
+            Index::Date(index) => index.inner.sort(items, direction, nulls),
+            Index::Enum(index) => index.inner.sort(items, direction, nulls),
+            Index::Bool(index) => index.inner.sort(items, direction, nulls),
         }
     }
 
@@ -138,6 +475,70 @@ impl Index {
         }
     }
 
+    /// Group the provided `items` by their value in the index, sorted by value. Items
+    /// without a value are not part of any group.
+    pub(crate) fn groups(&self, items: &RoaringBitmap) -> Vec<(FieldValue, RoaringBitmap)> {
+        match self {
+            Index::String(index) => index.groups(items),
+            Index::Numeric(index) => index.groups(items),
+            Index::Date(index) => index.groups(items),
+            Index::Enum(index) => index.groups(items),
+            Index::Bool(index) => index.groups(items),
+        }
+    }
+
+    /// Aggregate the values of the provided `items` in the index. `None` is returned
+    /// if none of the items has a value, except for `count`, which is always defined.
+    pub(crate) fn aggregate(
+        &self,
+        function: &AggregateFunction,
+        items: &RoaringBitmap,
+    ) -> Result<Option<FieldValue>, IndexError> {
+        let value = match function {
+            AggregateFunction::Count => Some(FieldValue::Integer(self.count(items))),
+            AggregateFunction::Sum | AggregateFunction::Avg => {
+                let Index::Numeric(index) = self else {
+                    return Err(IndexError::UnsupportedOperation {
+                        operation: function.to_string(),
+                    });
+                };
+
+                let (sum, count) = index.sum(items);
+                match (function, count) {
+                    (_, 0) => None,
+                    (AggregateFunction::Avg, count) => Some(FieldValue::dec(sum / count as f64)),
+                    _ => Some(FieldValue::dec(sum)),
+                }
+            }
+            AggregateFunction::Min => match self {
+                Index::String(index) => index.min(items),
+                Index::Numeric(index) => index.min(items),
+                Index::Date(index) => index.min(items),
+                Index::Enum(index) => index.min(items),
+                Index::Bool(index) => index.min(items),
+            },
+            AggregateFunction::Max => match self {
+                Index::String(index) => index.max(items),
+                Index::Numeric(index) => index.max(items),
+                Index::Date(index) => index.max(items),
+                Index::Enum(index) => index.max(items),
+                Index::Bool(index) => index.max(items),
+            },
+        };
+
+        Ok(value)
+    }
+
+    fn count(&self, items: &RoaringBitmap) -> u64 {
+        match self {
+            Index::String(index) => index.inner.count(items),
+            Index::Numeric(index) => index.inner.count(items),
+            Index::Date(index) => index.inner.count(items),
+            Index::Enum(index) => index.inner.count(items),
+            Index::Bool(index) => index.inner.count(items),
+        }
+    }
+
     pub(crate) fn put(&mut self, value: FieldValue, position: u32) -> Result<(), IndexError> {
         match self {
             Index::String(index) => index.put(value, position),
@@ -148,6 +549,30 @@ impl Index {
         }
     }
 
+    /// Mark the item in `position` as having no value in the index.
+    pub(crate) fn put_missing(&mut self, position: u32) {
+        match self {
+            Index::String(index) => index.inner.put_missing(position),
+            Index::Numeric(index) => index.inner.put_missing(position),
+            Index::Date(index) => index.inner.put_missing(position),
+            Index::Enum(index) => index.inner.put_missing(position),
+            Index::Bool(index) => index.inner.put_missing(position),
+        }
+    }
+
+    /// Count the provided `items` without a value in the index.
+    pub(crate) fn count_missing(&self, items: &RoaringBitmap) -> u64 {
+        let missing = match self {
+            Index::String(index) => &index.inner.missing,
+            Index::Numeric(index) => &index.inner.missing,
+            Index::Date(index) => &index.inner.missing,
+            Index::Enum(index) => &index.inner.missing,
+            Index::Bool(index) => &index.inner.missing,
+        };
+
+        missing.intersection_len(items)
+    }
+
     pub(crate) fn plus(&mut self, index: &Index) -> Result<(), IndexError> {
         match (self, index) {
             (Index::String(left), Index::String(right)) => left.plus(right),
@@ -207,6 +632,7 @@ impl Index {
 pub(crate) struct StringIndex {
     inner: SortableIndex<String>,
     term: Option<TermIndex>,
+    trigram: Option<TrigramIndex>,
 }
 
 impl StringIndex {
@@ -218,6 +644,7 @@ impl StringIndex {
         StringIndex {
             inner: SortableIndex::from_iter(arr),
             term: None,
+            trigram: None,
         }
     }
 
@@ -225,10 +652,8 @@ impl StringIndex {
         self.term = Some(term);
     }
 
-    fn get_value(&self, position: u32) -> Option<FieldValue> {
-        self.inner
-            .get_value(position)
-            .map(|value| FieldValue::str(value.as_str()))
+    fn set_trigram(&mut self, trigram: TrigramIndex) {
+        self.trigram = Some(trigram);
     }
 
     fn put(&mut self, value: FieldValue, position: u32) -> Result<(), IndexError> {
@@ -242,6 +667,10 @@ impl StringIndex {
             term.put(&value, position);
         }
 
+        if let Some(trigram) = self.trigram.as_mut() {
+            trigram.put(&value, position);
+        }
+
         self.inner.put(value, position);
 
         Ok(())
@@ -252,6 +681,10 @@ impl StringIndex {
             term.remove_item(&position);
         }
 
+        if let Some(trigram) = self.trigram.as_mut() {
+            trigram.remove_item(position);
+        }
+
         self.inner.remove_item(position);
     }
 
@@ -261,6 +694,10 @@ impl StringIndex {
         if let (Some(term), Some(other)) = (self.term.as_mut(), other.term.as_ref()) {
             term.plus(other)
         }
+
+        if let (Some(trigram), Some(other)) = (self.trigram.as_mut(), other.trigram.as_ref()) {
+            trigram.plus(other)
+        }
     }
 
     fn minus(&mut self, other: &StringIndex) {
@@ -269,6 +706,10 @@ impl StringIndex {
         if let (Some(term), Some(other)) = (self.term.as_mut(), other.term.as_ref()) {
             term.minus(other)
         }
+
+        if let (Some(trigram), Some(other)) = (self.trigram.as_mut(), other.trigram.as_ref()) {
+            trigram.minus(other)
+        }
     }
 
     fn counts(&self, items: &RoaringBitmap) -> BTreeMap<String, u64> {
@@ -278,6 +719,82 @@ impl StringIndex {
             .map(|(value, count)| (value.to_string(), count))
             .collect()
     }
+
+    /// Find the completions of a prefix within the `items`, together with the amount of
+    /// items containing each of them. The vocabulary of the term index is used, if present.
+    /// Otherwise, the values of the index are completed.
+    fn suggest(&self, prefix: &str, items: &RoaringBitmap) -> Vec<(String, u64)> {
+        if let Some(term) = self.term.as_ref() {
+            return term.suggest(prefix, items);
+        }
+
+        self.inner
+            .values
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(value, _)| value.starts_with(prefix))
+            .filter_map(|(value, bitmap)| {
+                let count = bitmap.intersection_len(items);
+                (count > 0).then(|| (value.clone(), count))
+            })
+            .collect()
+    }
+
+    fn score(&self, op: &FilterOperation, items: &RoaringBitmap, scores: &mut HashMap<u32, f64>) {
+        let Some(term) = self.term.as_ref() else {
+            return;
+        };
+
+        term.score(&term.searched_terms(op), items, scores);
+    }
+
+    /// Highlight the words of `value`, the value of the item at `position`, matching the
+    /// terms searched by the filter operation.
+    fn highlight(&self, op: &FilterOperation, position: u32, value: &str) -> Vec<Range<usize>> {
+        let Some(term) = self.term.as_ref() else {
+            return Vec::new();
+        };
+
+        let terms = term.searched_terms(op);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        // Term positions refer to the words of the value, in the order they were analyzed
+        let words = term.matched_words(&terms, position);
+        term.analyzer
+            .analyze_with_offsets(value)
+            .into_iter()
+            .enumerate()
+            .filter(|(word, _)| words.contains(word))
+            .map(|(_, (_, offsets))| offsets)
+            .collect()
+    }
+
+    fn correct(&self, op: &FilterOperation) -> Vec<Correction> {
+        let Some(term) = self.term.as_ref() else {
+            return Vec::new();
+        };
+
+        match op {
+            FilterOperation::Contains(value) | FilterOperation::Matches(value) => value
+                .as_string()
+                .map(|value| term.correct(value))
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl ValueIndex for StringIndex {
+    type Key = String;
+
+    fn sortable(&self) -> &SortableIndex<String> {
+        &self.inner
+    }
+
+    fn key_value(&self, key: &String) -> Option<FieldValue> {
+        Some(FieldValue::str(key.as_str()))
+    }
 }
 
 impl FilterableIndex for StringIndex {
@@ -298,15 +815,36 @@ impl FilterableIndex for StringIndex {
         Ok(hits)
     }
 
+    fn in_values(&self, values: &[FieldValue]) -> Result<RoaringBitmap, FilterError> {
+        let mut keys = Vec::with_capacity(values.len());
+        for value in values {
+            let Some(string_value) = value.as_string() else {
+                return Err(FilterError::InvalidInput {
+                    filter: FilterName::In,
+                    type_name: TypeName::String,
+                });
+            };
+            keys.push(string_value);
+        }
+
+        Ok(self.inner.get_any(keys))
+    }
+
     fn between(
         &self,
-        _: Bound<&FieldValue>,
-        _: Bound<&FieldValue>,
+        first: Bound<&FieldValue>,
+        second: Bound<&FieldValue>,
     ) -> Result<RoaringBitmap, FilterError> {
-        Err(FilterError::UnsupportedOperation {
-            filter: FilterName::Between,
-            type_name: TypeName::String,
-        })
+        let as_string = |value: &FieldValue| {
+            value.as_string().cloned().ok_or(FilterError::InvalidInput {
+                filter: FilterName::Between,
+                type_name: TypeName::String,
+            })
+        };
+
+        Ok(self
+            .inner
+            .range(map_bound(first, as_string)?, map_bound(second, as_string)?))
     }
 
     fn contains(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
@@ -338,54 +876,189 @@ impl FilterableIndex for StringIndex {
 
         Ok(term.match_phrase(string_value))
     }
-}
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
-pub(crate) struct NumericIndex {
-    inner: SortableIndex<OrderedFloat<f64>>,
-}
+    fn fuzzy(&self, value: &FieldValue, max_edits: u32) -> Result<RoaringBitmap, FilterError> {
+        let Some(term) = self.term.as_ref() else {
+            return Err(FilterError::MissingTermIndex);
+        };
 
-impl NumericIndex {
-    fn new() -> Self {
-        Self::default()
-    }
+        let Some(string_value) = value.as_string() else {
+            return Err(FilterError::InvalidInput {
+                filter: FilterName::Fuzzy,
+                type_name: TypeName::String,
+            });
+        };
 
-    fn from_iter<const N: usize>(arr: [(OrderedFloat<f64>, RoaringBitmap); N]) -> Self {
-        NumericIndex {
-            inner: SortableIndex::from_iter(arr),
-        }
+        Ok(term.fuzzy(string_value, max_edits))
     }
 
-    fn get_value(&self, position: u32) -> Option<FieldValue> {
-        self.inner
-            .get_value(position)
-            .map(|value| FieldValue::Decimal(*value))
+    fn near(
+        &self,
+        value: &FieldValue,
+        slop: u32,
+        ordered: bool,
+    ) -> Result<RoaringBitmap, FilterError> {
+        let Some(term) = self.term.as_ref() else {
+            return Err(FilterError::MissingTermIndex);
+        };
+
+        let Some(string_value) = value.as_string() else {
+            return Err(FilterError::InvalidInput {
+                filter: FilterName::Near,
+                type_name: TypeName::String,
+            });
+        };
+
+        Ok(term.match_near(string_value, slop, ordered))
     }
 
-    fn put(&mut self, value: FieldValue, position: u32) -> Result<(), IndexError> {
-        let value = match value {
-            FieldValue::Integer(value) => OrderedFloat(value as f64),
-            FieldValue::Decimal(value) => value,
-            _ => {
-                return Err(IndexError::UnexpectedValue {
-                    expected_type: TypeName::Numeric,
-                })
-            }
+    fn starts_with(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        let Some(prefix) = value.as_string() else {
+            return Err(FilterError::InvalidInput {
+                filter: FilterName::StartsWith,
+                type_name: TypeName::String,
+            });
         };
 
-        self.inner.put(value, position);
+        // Keys are sorted lexicographically, so that all the keys starting with the prefix
+        // are found consecutively right from the prefix itself.
+        let mut matches = RoaringBitmap::new();
+        for (_, bitmap) in self
+            .inner
+            .values
+            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(prefix.as_str()))
+        {
+            matches |= bitmap;
+        }
 
-        Ok(())
+        Ok(matches)
     }
 
-    fn plus(&mut self, other: &NumericIndex) {
-        self.inner.plus(&other.inner)
-    }
+    fn substring(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        let Some(trigram) = self.trigram.as_ref() else {
+            return Err(FilterError::MissingTrigramIndex);
+        };
+
+        let Some(substring) = value.as_string() else {
+            return Err(FilterError::InvalidInput {
+                filter: FilterName::Substring,
+                type_name: TypeName::String,
+            });
+        };
+
+        // Substrings too short to have trigrams need to be verified against every value
+        let Some(candidates) = trigram.candidates(substring) else {
+            return Ok(self
+                .inner
+                .values
+                .iter()
+                .filter(|(key, _)| key.contains(substring.as_str()))
+                .map(|(_, bitmap)| bitmap)
+                .union());
+        };
+
+        // Containing all the trigrams of the substring doesn't imply containing the substring
+        // itself, so that the value of each candidate is verified.
+        let matches = candidates
+            .into_iter()
+            .filter(|position| {
+                self.inner
+                    .get_value(*position)
+                    .is_some_and(|key| key.contains(substring.as_str()))
+            })
+            .collect();
+
+        Ok(matches)
+    }
+
+    /// Match the pattern against the distinct values of the index, as well as against the
+    /// vocabulary of its term index, if present.
+    fn matches_pattern(
+        &self,
+        pattern: &Pattern,
+        _: FilterName,
+    ) -> Result<RoaringBitmap, FilterError> {
+        let mut matches = RoaringBitmap::new();
+        for (_, bitmap) in self
+            .inner
+            .values
+            .range::<str, _>((Bound::Included(pattern.prefix.as_str()), Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(pattern.prefix.as_str()))
+            .filter(|(key, _)| pattern.is_match(key))
+        {
+            matches |= bitmap;
+        }
+
+        if let Some(term) = self.term.as_ref() {
+            matches |= term.matches_pattern(pattern);
+        }
+
+        Ok(matches)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct NumericIndex {
+    inner: SortableIndex<OrderedFloat<f64>>,
+    /// Whether a decimal value was put in the index, otherwise its values are read back
+    /// as integers.
+    decimal: bool,
+}
+
+impl NumericIndex {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn from_iter<const N: usize>(arr: [(OrderedFloat<f64>, RoaringBitmap); N]) -> Self {
+        NumericIndex {
+            inner: SortableIndex::from_iter(arr),
+            decimal: true,
+        }
+    }
+
+    fn put(&mut self, value: FieldValue, position: u32) -> Result<(), IndexError> {
+        let key = match value {
+            FieldValue::Integer(integer) => OrderedFloat(integer as f64),
+            FieldValue::Decimal(decimal) => decimal,
+            _ => {
+                return Err(IndexError::UnexpectedValue {
+                    expected_type: TypeName::Numeric,
+                })
+            }
+        };
+
+        self.inner.put(key, position);
+        self.decimal |= matches!(value, FieldValue::Decimal(_));
+
+        Ok(())
+    }
+
+    fn plus(&mut self, other: &NumericIndex) {
+        self.inner.plus(&other.inner);
+        self.decimal |= other.decimal;
+    }
 
     fn minus(&mut self, other: &NumericIndex) {
         self.inner.minus(&other.inner)
     }
 
+    /// Sum the values of the provided `items`, together with the amount of items
+    /// with a value.
+    fn sum(&self, items: &RoaringBitmap) -> (f64, u64) {
+        let mut sum = 0.0;
+        let mut count = 0;
+
+        for (value, bitmap) in &self.inner.values {
+            let matches = bitmap.intersection_len(items);
+            sum += value.into_inner() * matches as f64;
+            count += matches;
+        }
+
+        (sum, count)
+    }
+
     fn counts(&self, items: &RoaringBitmap) -> BTreeMap<String, u64> {
         self.inner
             .counts(items)
@@ -395,9 +1068,31 @@ impl NumericIndex {
     }
 }
 
+impl ValueIndex for NumericIndex {
+    type Key = OrderedFloat<f64>;
+
+    fn sortable(&self) -> &SortableIndex<OrderedFloat<f64>> {
+        &self.inner
+    }
+
+    fn key_value(&self, key: &OrderedFloat<f64>) -> Option<FieldValue> {
+        Some(FieldValue::Decimal(*key))
+    }
+
+    fn get_value(&self, position: u32) -> Option<FieldValue> {
+        let key = self.inner.get_value(position)?;
+
+        if self.decimal {
+            Some(FieldValue::Decimal(*key))
+        } else {
+            Some(FieldValue::Integer(key.into_inner() as u64))
+        }
+    }
+}
+
 impl FilterableIndex for NumericIndex {
     fn equal(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
-        let Some(numeric_value) = value.as_decimal() else {
+        let Some(numeric_value) = value.as_number() else {
             return Err(FilterError::InvalidInput {
                 filter: FilterName::Eq,
                 type_name: TypeName::Numeric,
@@ -406,36 +1101,44 @@ impl FilterableIndex for NumericIndex {
 
         let hits = self
             .inner
-            .get(numeric_value)
+            .get(&numeric_value)
             .cloned()
             .unwrap_or_else(RoaringBitmap::new);
 
         Ok(hits)
     }
 
+    fn in_values(&self, values: &[FieldValue]) -> Result<RoaringBitmap, FilterError> {
+        let mut keys = Vec::with_capacity(values.len());
+        for value in values {
+            let Some(numeric_value) = value.as_number() else {
+                return Err(FilterError::InvalidInput {
+                    filter: FilterName::In,
+                    type_name: TypeName::Numeric,
+                });
+            };
+            keys.push(numeric_value);
+        }
+
+        Ok(self.inner.get_any(keys.iter()))
+    }
+
     fn between(
         &self,
         first: Bound<&FieldValue>,
         second: Bound<&FieldValue>,
     ) -> Result<RoaringBitmap, FilterError> {
-        let first_bound = first.map(|value| {
-            value
-                .as_decimal()
-                .expect("Invalid \"between\" filter value. Expected numeric value.")
-        });
-
-        let second_bound = second.map(|value| {
-            value
-                .as_decimal()
-                .expect("Invalid \"between\" filter value. Expected numeric value.")
-        });
-
-        let mut matches = RoaringBitmap::new();
-        for (_, bitmap) in self.inner.0.range((first_bound, second_bound)) {
-            matches |= bitmap;
-        }
+        let as_decimal = |value: &FieldValue| {
+            value.as_number().ok_or(FilterError::InvalidInput {
+                filter: FilterName::Between,
+                type_name: TypeName::Numeric,
+            })
+        };
 
-        Ok(matches)
+        Ok(self.inner.range(
+            map_bound(first, as_decimal)?,
+            map_bound(second, as_decimal)?,
+        ))
     }
 
     fn contains(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
@@ -445,6 +1148,45 @@ impl FilterableIndex for NumericIndex {
     fn matches(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
         self.equal(value)
     }
+
+    fn starts_with(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::StartsWith,
+            type_name: TypeName::Numeric,
+        })
+    }
+
+    fn substring(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Substring,
+            type_name: TypeName::Numeric,
+        })
+    }
+
+    fn fuzzy(&self, _: &FieldValue, _: u32) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Fuzzy,
+            type_name: TypeName::Numeric,
+        })
+    }
+
+    fn near(&self, _: &FieldValue, _: u32, _: bool) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Near,
+            type_name: TypeName::Numeric,
+        })
+    }
+
+    fn matches_pattern(
+        &self,
+        _: &Pattern,
+        filter: FilterName,
+    ) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter,
+            type_name: TypeName::Numeric,
+        })
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -463,35 +1205,22 @@ impl DateIndex {
         }
     }
 
+    /// Timestamp of a date value, or `None` if the value is not a valid date.
     fn parse_value(value: &FieldValue) -> Option<i64> {
         match value {
-            FieldValue::String(string) => {
-                let date = parse_date(string)
-                    .unwrap_or_else(|err| panic!("Date could not be parsed: {}", err));
-
-                Some(date_to_timestamp(date))
-            }
+            FieldValue::String(string) => parse_date(string).ok().map(date_to_timestamp),
             _ => None,
         }
     }
 
-    fn get_value(&self, position: u32) -> Option<FieldValue> {
-        let value = self.inner.get_value(position)?;
-        let date = timestamp_to_date(*value)
-            .format(&Iso8601::DEFAULT)
-            .unwrap_or_else(|err| panic!("Date could not be formatted: {}", err));
-
-        Some(FieldValue::String(date))
-    }
-
     fn put(&mut self, value: FieldValue, position: u32) -> Result<(), IndexError> {
-        let Some(value) = DateIndex::parse_value(&value) else {
+        let Some(key) = DateIndex::parse_value(&value) else {
             return Err(IndexError::UnexpectedValue {
                 expected_type: TypeName::Date,
             });
         };
 
-        self.inner.put(value, position);
+        self.inner.put(key, position);
 
         Ok(())
     }
@@ -505,6 +1234,22 @@ impl DateIndex {
     }
 }
 
+impl ValueIndex for DateIndex {
+    type Key = i64;
+
+    fn sortable(&self) -> &SortableIndex<i64> {
+        &self.inner
+    }
+
+    fn key_value(&self, key: &i64) -> Option<FieldValue> {
+        let date = timestamp_to_date(*key)
+            .format(&Iso8601::DATE)
+            .unwrap_or_else(|err| panic!("Date could not be formatted: {}", err));
+
+        Some(FieldValue::String(date))
+    }
+}
+
 impl FilterableIndex for DateIndex {
     fn equal(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
         let Some(date_value) = DateIndex::parse_value(value) else {
@@ -523,27 +1268,36 @@ impl FilterableIndex for DateIndex {
         Ok(hits)
     }
 
+    fn in_values(&self, values: &[FieldValue]) -> Result<RoaringBitmap, FilterError> {
+        let mut keys = Vec::with_capacity(values.len());
+        for value in values {
+            let Some(date_value) = DateIndex::parse_value(value) else {
+                return Err(FilterError::InvalidInput {
+                    filter: FilterName::In,
+                    type_name: TypeName::Date,
+                });
+            };
+            keys.push(date_value);
+        }
+
+        Ok(self.inner.get_any(&keys))
+    }
+
     fn between(
         &self,
         first: Bound<&FieldValue>,
         second: Bound<&FieldValue>,
     ) -> Result<RoaringBitmap, FilterError> {
-        let first_bound = first.map(|value| {
-            DateIndex::parse_value(value)
-                .expect("Invalid \"between\" filter value. Expected date value.")
-        });
-
-        let second_bound = second.map(|value| {
-            DateIndex::parse_value(value)
-                .expect("Invalid \"between\" filter value. Expected date value.")
-        });
-
-        let mut matches = RoaringBitmap::new();
-        for (_, bitmap) in self.inner.0.range((first_bound, second_bound)) {
-            matches |= bitmap;
-        }
+        let as_date = |value: &FieldValue| {
+            DateIndex::parse_value(value).ok_or(FilterError::InvalidInput {
+                filter: FilterName::Between,
+                type_name: TypeName::Date,
+            })
+        };
 
-        Ok(matches)
+        Ok(self
+            .inner
+            .range(map_bound(first, as_date)?, map_bound(second, as_date)?))
     }
 
     fn contains(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
@@ -553,6 +1307,45 @@ impl FilterableIndex for DateIndex {
     fn matches(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
         self.equal(value)
     }
+
+    fn starts_with(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::StartsWith,
+            type_name: TypeName::Date,
+        })
+    }
+
+    fn substring(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Substring,
+            type_name: TypeName::Date,
+        })
+    }
+
+    fn fuzzy(&self, _: &FieldValue, _: u32) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Fuzzy,
+            type_name: TypeName::Date,
+        })
+    }
+
+    fn near(&self, _: &FieldValue, _: u32, _: bool) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Near,
+            type_name: TypeName::Date,
+        })
+    }
+
+    fn matches_pattern(
+        &self,
+        _: &Pattern,
+        filter: FilterName,
+    ) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter,
+            type_name: TypeName::Date,
+        })
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -579,13 +1372,6 @@ impl EnumIndex {
         }
     }
 
-    fn get_value(&self, position: u32) -> Option<FieldValue> {
-        self.inner
-            .get_value(position)
-            .and_then(|value| self.values.get_index(*value))
-            .map(|value| FieldValue::str(value.as_str()))
-    }
-
     fn put(&mut self, value: FieldValue, position: u32) -> Result<(), IndexError> {
         let Some(value) = value.as_string() else {
             return Err(IndexError::UnexpectedValue {
@@ -625,6 +1411,20 @@ impl EnumIndex {
     }
 }
 
+impl ValueIndex for EnumIndex {
+    type Key = usize;
+
+    fn sortable(&self) -> &SortableIndex<usize> {
+        &self.inner
+    }
+
+    fn key_value(&self, key: &usize) -> Option<FieldValue> {
+        self.values
+            .get_index(*key)
+            .map(|value| FieldValue::str(value.as_str()))
+    }
+}
+
 impl FilterableIndex for EnumIndex {
     fn equal(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
         let Some(string_value) = value.as_string() else {
@@ -650,12 +1450,37 @@ impl FilterableIndex for EnumIndex {
         Ok(hits)
     }
 
+    fn in_values(&self, values: &[FieldValue]) -> Result<RoaringBitmap, FilterError> {
+        let mut keys = Vec::with_capacity(values.len());
+        for value in values {
+            let Some(string_value) = value.as_string() else {
+                return Err(FilterError::InvalidInput {
+                    filter: FilterName::In,
+                    type_name: TypeName::Enum,
+                });
+            };
+
+            let Some(index) = self.values.get_index_of(string_value) else {
+                return Err(FilterError::UnknownEnumValue {
+                    filter: FilterName::In,
+                    value: string_value.to_string(),
+                });
+            };
+            keys.push(index);
+        }
+
+        Ok(self.inner.get_any(&keys))
+    }
+
     fn between(
         &self,
         _: Bound<&FieldValue>,
         _: Bound<&FieldValue>,
     ) -> Result<RoaringBitmap, FilterError> {
-        panic!("Unsupported filter operation \"between\" for enum index")
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Between,
+            type_name: TypeName::Enum,
+        })
     }
 
     fn contains(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
@@ -665,6 +1490,45 @@ impl FilterableIndex for EnumIndex {
     fn matches(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
         self.equal(value)
     }
+
+    fn starts_with(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::StartsWith,
+            type_name: TypeName::Enum,
+        })
+    }
+
+    fn substring(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Substring,
+            type_name: TypeName::Enum,
+        })
+    }
+
+    fn fuzzy(&self, _: &FieldValue, _: u32) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Fuzzy,
+            type_name: TypeName::Enum,
+        })
+    }
+
+    fn near(&self, _: &FieldValue, _: u32, _: bool) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Near,
+            type_name: TypeName::Enum,
+        })
+    }
+
+    fn matches_pattern(
+        &self,
+        _: &Pattern,
+        filter: FilterName,
+    ) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter,
+            type_name: TypeName::Enum,
+        })
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -685,12 +1549,6 @@ impl BoolIndex {
         }
     }
 
-    fn get_value(&self, position: u32) -> Option<FieldValue> {
-        self.inner
-            .get_value(position)
-            .map(|value| FieldValue::Bool(*value))
-    }
-
     fn put(&mut self, value: FieldValue, position: u32) -> Result<(), IndexError> {
         let Some(value) = value.get_bool() else {
             return Err(IndexError::UnexpectedValue {
@@ -720,6 +1578,18 @@ impl BoolIndex {
     }
 }
 
+impl ValueIndex for BoolIndex {
+    type Key = bool;
+
+    fn sortable(&self) -> &SortableIndex<bool> {
+        &self.inner
+    }
+
+    fn key_value(&self, key: &bool) -> Option<FieldValue> {
+        Some(FieldValue::Bool(*key))
+    }
+}
+
 impl FilterableIndex for BoolIndex {
     fn equal(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
         let Some(bool_value) = value.as_bool() else {
@@ -738,12 +1608,30 @@ impl FilterableIndex for BoolIndex {
         Ok(hits)
     }
 
+    fn in_values(&self, values: &[FieldValue]) -> Result<RoaringBitmap, FilterError> {
+        let mut keys = Vec::with_capacity(values.len());
+        for value in values {
+            let Some(bool_value) = value.as_bool() else {
+                return Err(FilterError::InvalidInput {
+                    filter: FilterName::In,
+                    type_name: TypeName::Bool,
+                });
+            };
+            keys.push(bool_value);
+        }
+
+        Ok(self.inner.get_any(keys))
+    }
+
     fn between(
         &self,
         _: Bound<&FieldValue>,
         _: Bound<&FieldValue>,
     ) -> Result<RoaringBitmap, FilterError> {
-        panic!("Unsupported filter operation \"between\" for bool index")
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Between,
+            type_name: TypeName::Bool,
+        })
     }
 
     fn contains(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
@@ -753,44 +1641,170 @@ impl FilterableIndex for BoolIndex {
     fn matches(&self, value: &FieldValue) -> Result<RoaringBitmap, FilterError> {
         self.equal(value)
     }
-}
-
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-struct SortableIndex<T: Ord>(BTreeMap<T, RoaringBitmap>);
 
-impl<T: Ord + Clone> SortableIndex<T> {
-    fn from_iter<const N: usize>(arr: [(T, RoaringBitmap); N]) -> Self {
-        SortableIndex(BTreeMap::from(arr))
+    fn starts_with(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::StartsWith,
+            type_name: TypeName::Bool,
+        })
     }
 
-    /// Sort the provided `items` by a certain direction
-    fn sort(&self, items: &RoaringBitmap, direction: &SortDirection) -> Vec<u32> {
-        match direction {
-            SortDirection::ASC => SortableIndex::<T>::sort_by_iter(items, self.0.values()),
-            SortDirection::DESC => SortableIndex::<T>::sort_by_iter(items, self.0.values().rev()),
-        }
+    fn substring(&self, _: &FieldValue) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Substring,
+            type_name: TypeName::Bool,
+        })
     }
 
-    fn sort_by_iter<'a, I>(items: &RoaringBitmap, ordered_bitmaps: I) -> Vec<u32>
-    where
+    fn fuzzy(&self, _: &FieldValue, _: u32) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Fuzzy,
+            type_name: TypeName::Bool,
+        })
+    }
+
+    fn near(&self, _: &FieldValue, _: u32, _: bool) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter: FilterName::Near,
+            type_name: TypeName::Bool,
+        })
+    }
+
+    fn matches_pattern(
+        &self,
+        _: &Pattern,
+        filter: FilterName,
+    ) -> Result<RoaringBitmap, FilterError> {
+        Err(FilterError::UnsupportedOperation {
+            filter,
+            type_name: TypeName::Bool,
+        })
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredSortableIndex<T>")]
+#[serde(bound(deserialize = "T: Ord + Clone + Deserialize<'de>"))]
+struct SortableIndex<T: Ord> {
+    values: BTreeMap<T, RoaringBitmap>,
+    /// Positions of the items without a value.
+    missing: RoaringBitmap,
+    /// Key of each position with a value, to read the value of an item without scanning
+    /// all the keys. Not stored, as it is rebuilt from the values.
+    #[serde(skip)]
+    keys: HashMap<u32, T>,
+}
+
+/// The keys of each position are built from the values, so they are not compared.
+impl<T: Ord> PartialEq for SortableIndex<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values && self.missing == other.missing
+    }
+}
+
+/// The stored fields of a [`SortableIndex`].
+#[derive(Deserialize)]
+struct StoredSortableIndex<T: Ord> {
+    values: BTreeMap<T, RoaringBitmap>,
+    missing: RoaringBitmap,
+}
+
+impl<T: Ord + Clone> From<StoredSortableIndex<T>> for SortableIndex<T> {
+    fn from(stored: StoredSortableIndex<T>) -> Self {
+        let keys = stored
+            .values
+            .iter()
+            .flat_map(|(key, bitmap)| bitmap.iter().map(move |position| (position, key.clone())))
+            .collect();
+
+        SortableIndex {
+            values: stored.values,
+            missing: stored.missing,
+            keys,
+        }
+    }
+}
+
+impl<T: Ord + Clone> SortableIndex<T> {
+    fn from_iter<const N: usize>(arr: [(T, RoaringBitmap); N]) -> Self {
+        SortableIndex::from(StoredSortableIndex {
+            values: BTreeMap::from(arr),
+            missing: RoaringBitmap::new(),
+        })
+    }
+
+    /// Positions of the items with a value within the bounds. Ranges with a start greater
+    /// than their end are empty, instead of making `BTreeMap::range` panic.
+    fn range(&self, first: Bound<T>, second: Bound<T>) -> RoaringBitmap {
+        let is_empty = match (&first, &second) {
+            (Bound::Included(first), Bound::Included(second)) => first > second,
+            (
+                Bound::Included(first) | Bound::Excluded(first),
+                Bound::Included(second) | Bound::Excluded(second),
+            ) => first >= second,
+            _ => false,
+        };
+
+        if is_empty {
+            return RoaringBitmap::new();
+        }
+
+        self.values
+            .range((first, second))
+            .map(|(_, bitmap)| bitmap)
+            .union()
+    }
+
+    /// Sort the provided `items` by a certain direction. The items are returned grouped
+    /// in bitmaps of equal values, so that further sorting can refine each group. Items
+    /// without a value are grouped together and placed depending on `nulls`.
+    fn sort(
+        &self,
+        items: &RoaringBitmap,
+        direction: &SortDirection,
+        nulls: &NullsPlacement,
+    ) -> Vec<RoaringBitmap> {
+        match direction {
+            SortDirection::ASC => {
+                SortableIndex::<T>::sort_by_iter(items, self.values.values(), nulls)
+            }
+            SortDirection::DESC => {
+                SortableIndex::<T>::sort_by_iter(items, self.values.values().rev(), nulls)
+            }
+        }
+    }
+
+    fn sort_by_iter<'a, I>(
+        items: &RoaringBitmap,
+        ordered_bitmaps: I,
+        nulls: &NullsPlacement,
+    ) -> Vec<RoaringBitmap>
+    where
         I: Iterator<Item = &'a RoaringBitmap>,
     {
         let mut sorted = Vec::new();
-        let mut found = Vec::new();
 
         // Iterate over the tree of sorted values in the index
         for bitmap in ordered_bitmaps {
             // Intersection between the value items and the input
             let round = items & bitmap;
 
-            sorted.extend(&round);
-            found.push(round);
+            if !round.is_empty() {
+                sorted.push(round);
+            }
         }
 
         // Compute elements not present in the index by subtracting all the found elements
         // from the input. Use `union` for a faster union of the bitmaps instead of applying
         // the `BitOr` operation manually.
-        sorted.extend(items - found.union());
+        let missing = items - sorted.iter().union();
+
+        if !missing.is_empty() {
+            match nulls {
+                NullsPlacement::First => sorted.insert(0, missing),
+                NullsPlacement::Last => sorted.push(missing),
+            }
+        }
 
         sorted
     }
@@ -798,58 +1812,217 @@ impl<T: Ord + Clone> SortableIndex<T> {
     fn counts(&self, items: &RoaringBitmap) -> Vec<(&T, u64)> {
         let mut counts = Vec::new();
 
-        for (value, bitmap) in &self.0 {
+        for (value, bitmap) in &self.values {
             counts.push((value, bitmap.intersection_len(items)))
         }
 
         counts
     }
 
-    fn get_value(&self, position: u32) -> Option<&T> {
-        for (value, bitmap) in &self.0 {
-            if bitmap.contains(position) {
-                return Some(value);
+    /// Group the provided `items` by their value, skipping the values without any item.
+    fn groups(&self, items: &RoaringBitmap) -> Vec<(&T, RoaringBitmap)> {
+        let mut groups = Vec::new();
+
+        for (value, bitmap) in &self.values {
+            let group = bitmap & items;
+
+            if !group.is_empty() {
+                groups.push((value, group));
             }
         }
-        None
+
+        groups
+    }
+
+    /// Count the provided `items` with a value in the index.
+    fn count(&self, items: &RoaringBitmap) -> u64 {
+        self.values
+            .values()
+            .map(|bitmap| bitmap.intersection_len(items))
+            .sum()
+    }
+
+    /// Get the lowest value of the provided `items`.
+    fn min(&self, items: &RoaringBitmap) -> Option<&T> {
+        self.values
+            .iter()
+            .find(|(_, bitmap)| !bitmap.is_disjoint(items))
+            .map(|(value, _)| value)
+    }
+
+    /// Get the highest value of the provided `items`.
+    fn max(&self, items: &RoaringBitmap) -> Option<&T> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(_, bitmap)| !bitmap.is_disjoint(items))
+            .map(|(value, _)| value)
+    }
+
+    fn get_value(&self, position: u32) -> Option<&T> {
+        self.keys.get(&position)
     }
 
     fn get(&self, key: &T) -> Option<&RoaringBitmap> {
-        self.0.get(key)
+        self.values.get(key)
+    }
+
+    /// Get the union of the bitmaps for all the provided keys. Unknown keys are ignored.
+    fn get_any<'a, I>(&self, keys: I) -> RoaringBitmap
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        keys.into_iter()
+            .filter_map(|key| self.values.get(key))
+            .union()
+    }
+
+    /// Get the union of the bitmaps for all the values, i.e. the positions of the items
+    /// with a value.
+    fn present(&self) -> RoaringBitmap {
+        self.values.values().union()
     }
 
     fn put(&mut self, key: T, position: u32) {
-        let bitmap = self.0.entry(key).or_default();
+        let bitmap = self.values.entry(key.clone()).or_default();
         bitmap.insert(position);
+
+        self.keys.insert(position, key);
+        self.missing.remove(position);
+    }
+
+    fn put_missing(&mut self, position: u32) {
+        self.missing.insert(position);
     }
 
     fn plus(&mut self, other: &SortableIndex<T>) {
-        for (key, right) in &other.0 {
-            if let Some(left) = self.0.get_mut(key) {
+        for (key, right) in &other.values {
+            if let Some(left) = self.values.get_mut(key) {
                 *left |= right;
             } else {
-                self.0.insert(key.clone(), right.clone());
+                self.values.insert(key.clone(), right.clone());
             }
         }
+
+        for (position, key) in &other.keys {
+            self.keys.insert(*position, key.clone());
+        }
+
+        self.missing |= &other.missing;
     }
 
     fn minus(&mut self, other: &SortableIndex<T>) {
-        for (key, right) in &other.0 {
-            if let Some(left) = self.0.get_mut(key) {
+        for (key, right) in &other.values {
+            if let Some(left) = self.values.get_mut(key) {
                 *left -= right;
 
                 if left.is_empty() {
-                    self.0.remove(key);
+                    self.values.remove(key);
+                }
+            }
+
+            for position in right {
+                if self.keys.get(&position) == Some(key) {
+                    self.keys.remove(&position);
                 }
             }
         }
+
+        self.missing -= &other.missing;
+    }
+
+    fn remove_item(&mut self, position: u32) {
+        if let Some(key) = self.keys.remove(&position) {
+            if let Some(bitmap) = self.values.get_mut(&key) {
+                bitmap.remove(position);
+            }
+        }
+
+        self.missing.remove(position);
+    }
+}
+
+/// An index of the trigrams (sequences of three characters) of each value, allowing to find
+/// the values that may contain a substring without comparing all of them.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TrigramIndex {
+    inner: HashMap<String, RoaringBitmap>,
+}
+
+impl TrigramIndex {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn put(&mut self, value: &str, position: u32) {
+        for trigram in trigrams(value) {
+            self.inner.entry(trigram).or_default().insert(position);
+        }
     }
 
     fn remove_item(&mut self, position: u32) {
-        for bitmap in self.0.values_mut() {
+        self.inner.retain(|_, bitmap| {
             bitmap.remove(position);
+            !bitmap.is_empty()
+        });
+    }
+
+    fn plus(&mut self, other: &TrigramIndex) {
+        for (trigram, other_bitmap) in &other.inner {
+            *self.inner.entry(trigram.clone()).or_default() |= other_bitmap;
         }
     }
+
+    fn minus(&mut self, other: &TrigramIndex) {
+        for (trigram, other_bitmap) in &other.inner {
+            if let Some(bitmap) = self.inner.get_mut(trigram) {
+                *bitmap -= other_bitmap;
+
+                if bitmap.is_empty() {
+                    self.inner.remove(trigram);
+                }
+            }
+        }
+    }
+
+    /// Find the positions of the values containing every trigram of the substring, which
+    /// are the candidates to contain the substring. Substrings shorter than a trigram have
+    /// no candidates to be narrowed down to.
+    fn candidates(&self, substring: &str) -> Option<RoaringBitmap> {
+        let trigrams = trigrams(substring);
+        if trigrams.is_empty() {
+            return None;
+        }
+
+        Some(
+            trigrams
+                .iter()
+                .map(|trigram| self.inner.get(trigram).cloned().unwrap_or_default())
+                .intersection(),
+        )
+    }
+}
+
+/// Convert the value of a filter bound, failing if the value has the wrong type.
+fn map_bound<T>(
+    bound: Bound<&FieldValue>,
+    convert: impl Fn(&FieldValue) -> Result<T, FilterError>,
+) -> Result<Bound<T>, FilterError> {
+    Ok(match bound {
+        Bound::Included(value) => Bound::Included(convert(value)?),
+        Bound::Excluded(value) => Bound::Excluded(convert(value)?),
+        Bound::Unbounded => Bound::Unbounded,
+    })
+}
+
+/// Split a value into its distinct trigrams.
+fn trigrams(value: &str) -> HashSet<String> {
+    let chars: Vec<char> = value.chars().collect();
+    chars
+        .windows(3)
+        .map(|window| window.iter().collect())
+        .collect()
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -886,9 +2059,50 @@ impl<const N: usize> From<[(u32, HashSet<usize>); N]> for TermPositions {
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// BM25 term frequency saturation parameter.
+const BM25_K1: f64 = 1.2;
+
+/// BM25 document length normalization parameter.
+const BM25_B: f64 = 0.75;
+
+/// Maximum amount of distinctive terms of a document used to find similar documents.
+const MAX_SIMILAR_TERMS: usize = 25;
+
+/// Weight of a term by how rare it is among the `documents`, given the amount of documents
+/// containing it, as defined by BM25.
+fn inverse_document_frequency(documents: f64, frequency: f64) -> f64 {
+    (1.0 + (documents - frequency + 0.5) / (frequency + 0.5)).ln()
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TermIndex {
-    inner: HashMap<String, TermPositions>,
+    /// Positions of each term, sorted by term to allow looking up terms by prefix.
+    inner: BTreeMap<String, TermPositions>,
+    /// Amount of terms stored for each document position, used for ranking.
+    lengths: HashMap<u32, u32>,
+    /// Sum of all the `lengths`, to compute the average document length without
+    /// iterating them.
+    total_length: u64,
+    analyzer: Analyzer,
+    /// Synonyms searched together with each term. They are not stored with the index,
+    /// but set after reading it, so that changing them doesn't require reindexing.
+    #[serde(skip)]
+    synonyms: HashMap<String, Vec<String>>,
+    /// Tree of the vocabulary to find the terms close to a word, built on the first search
+    /// and discarded whenever the vocabulary changes.
+    #[serde(skip)]
+    vocabulary: OnceLock<BkTree>,
+}
+
+/// The tree of the vocabulary is built from the terms, so it is not compared.
+impl PartialEq for TermIndex {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+            && self.lengths == other.lengths
+            && self.total_length == other.total_length
+            && self.analyzer == other.analyzer
+            && self.synonyms == other.synonyms
+    }
 }
 
 impl TermIndex {
@@ -897,96 +2111,425 @@ impl TermIndex {
         Self::default()
     }
 
+    /// Build a new `TermIndex` instance analyzing its content with the given analyzer.
+    pub(crate) fn with_analyzer(analyzer: Analyzer) -> Self {
+        TermIndex {
+            analyzer,
+            ..Self::new()
+        }
+    }
+
     /// Build a new index based a given an iterator of words and term positions.
     /// This is meant only to be used for assertions. This function assumes that
     /// the data provided is correct.
     pub(crate) fn from_iter<const N: usize>(pairs: [(&str, TermPositions); N]) -> Self {
         let mut index = Self::new();
         for (word, term_position) in pairs {
-            let Some(word) = Self::normalize(word) else {
+            let Some(word) = index.analyzer.analyze(word).into_iter().next() else {
                 continue;
             };
+            for (position, indices) in &term_position.0 {
+                *index.lengths.entry(*position).or_default() += indices.len() as u32;
+                index.total_length += indices.len() as u64;
+            }
             index.inner.insert(word, term_position);
         }
         index
     }
 
-    /// Check that a word is present in the index.
+    /// Check that a word is present in the index. In case the analyzer splits the word
+    /// into multiple terms, all of them must be present.
     pub(crate) fn contains(&self, word: &str) -> RoaringBitmap {
-        let mut hits = RoaringBitmap::new();
-
-        let Some(term_positions) = Self::normalize(word).and_then(|word| self.inner.get(&word))
-        else {
-            return hits;
-        };
-
-        for position in term_positions.0.keys() {
-            hits.insert(*position);
+        let terms = self.analyze(word);
+        if terms.is_empty() {
+            return RoaringBitmap::new();
         }
 
-        hits
+        terms
+            .iter()
+            .map(|term| match self.term_positions(term) {
+                Some(term_positions) => RoaringBitmap::from_iter(term_positions.0.keys()),
+                None => RoaringBitmap::new(),
+            })
+            .intersection()
     }
 
     /// Match the terms with a complete phrase so that all the words in the
     /// phrase must be present and in the same order. It returns all the positions
     /// of the matching documents in a bitmap.
     pub(crate) fn match_phrase(&self, phrase: &str) -> RoaringBitmap {
-        let mut word_consecutive_matches = HashMap::<u32, HashSet<usize>>::new();
+        self.match_near(phrase, 0, true)
+    }
 
-        // Iterate over each word from the input phrase
-        for word in phrase.split_whitespace().filter_map(Self::normalize) {
-            // Get the positions of the given term and their respective index
-            let Some(current_word_matches) = self.inner.get(&word) else {
+    /// Match the terms of a phrase occurring close to each other, so that all the words
+    /// in the phrase are present within a window of positions. `slop` defines how many
+    /// other positions the window may span, on top of the words in the phrase. If
+    /// `ordered`, the words must also appear in the same order as in the phrase.
+    pub(crate) fn match_near(&self, phrase: &str, slop: u32, ordered: bool) -> RoaringBitmap {
+        let words = self.analyze(phrase);
+        if words.is_empty() {
+            return RoaringBitmap::new();
+        }
+
+        let mut word_matches = Vec::with_capacity(words.len());
+        for word in &words {
+            let Some(term_positions) = self.term_positions(word) else {
                 return RoaringBitmap::new();
             };
+            word_matches.push(term_positions);
+        }
 
-            // For each matching word, refresh the consecutive matches so that only indices that
-            // have their previous already present in the result are returned.
-            let mut appended_positions = HashSet::with_capacity(current_word_matches.0.len());
-            for (position, term_indices) in &current_word_matches.0 {
-                if let Some(previous_indices) = word_consecutive_matches.get_mut(position) {
-                    // A document position has already been found, but no indices were stored.
-                    // This is an invalid state, it should not happen.
-                    assert!(!previous_indices.is_empty());
-
-                    // Generate new indices based on the previous incides so that only the ones with
-                    // a consecutive term in the current match survive.
-                    let mut new_indices = HashSet::<usize>::with_capacity(previous_indices.len());
-                    for term_index in term_indices {
-                        if previous_indices.contains(&(term_index - 1)) {
-                            new_indices.insert(term_index - 1);
-                            new_indices.insert(*term_index);
-                        }
-                    }
+        // Only the documents containing every word can match the phrase
+        let candidates = word_matches
+            .iter()
+            .map(|term_positions| RoaringBitmap::from_iter(term_positions.0.keys()))
+            .intersection();
+
+        let max_span = words.len() - 1 + slop as usize;
+
+        let mut hits = RoaringBitmap::new();
+        for position in candidates {
+            let word_indices: Vec<Vec<usize>> = word_matches
+                .iter()
+                .map(|term_positions| {
+                    let mut indices = Vec::from_iter(term_positions.0[&position].iter().copied());
+                    indices.sort_unstable();
+                    indices
+                })
+                .collect();
+
+            let span = if ordered {
+                ordered_span(&word_indices)
+            } else {
+                unordered_span(&words, &word_indices)
+            };
+
+            if span.is_some_and(|span| span <= max_span) {
+                hits.insert(position);
+            }
+        }
+
+        hits
+    }
 
-                    *previous_indices = new_indices;
-                } else {
-                    // The document position is unknown, this is the first iteration
-                    word_consecutive_matches.insert(*position, term_indices.clone());
+    /// Find the documents containing any word within `max_edits` edits (insertions, deletions
+    /// or substitutions) of the given word. In case the analyzer splits the word into multiple
+    /// terms, a close word must be present for each of them.
+    pub(crate) fn fuzzy(&self, word: &str, max_edits: u32) -> RoaringBitmap {
+        let terms = self.analyze(word);
+        if terms.is_empty() {
+            return RoaringBitmap::new();
+        }
+
+        terms
+            .iter()
+            .map(|term| {
+                let mut hits = RoaringBitmap::new();
+                for (_, term_positions) in self.fuzzy_candidates(term, max_edits) {
+                    hits.extend(term_positions.0.keys());
                 }
+                hits
+            })
+            .intersection()
+    }
+
+    /// Terms of the vocabulary within `max_edits` of the given term, sorted by term.
+    fn fuzzy_candidates(&self, term: &str, max_edits: u32) -> Vec<(&String, &TermPositions)> {
+        self.close_terms(term, max_edits as usize)
+            .into_iter()
+            .filter_map(|(candidate, _)| self.inner.get_key_value(candidate))
+            .collect()
+    }
+
+    /// Terms of the vocabulary within `max_edits` of the given term together with their
+    /// edit distance, sorted by term.
+    fn close_terms(&self, term: &str, max_edits: usize) -> Vec<(&str, usize)> {
+        let vocabulary = self
+            .vocabulary
+            .get_or_init(|| BkTree::from_iter(self.inner.keys()));
+
+        let word: Vec<char> = term.chars().collect();
+        let mut terms = vocabulary.find(&word, max_edits);
+        terms.sort_unstable();
+        terms
+    }
+
+    /// Terms of the index searched by a filter operation: the analyzed terms of its value,
+    /// or the terms of the vocabulary close to them for fuzzy searches.
+    pub(crate) fn searched_terms(&self, op: &FilterOperation) -> Vec<String> {
+        match op {
+            FilterOperation::Contains(value)
+            | FilterOperation::Matches(value)
+            | FilterOperation::Near { value, .. } => value
+                .as_string()
+                .map(|value| self.analyze(value))
+                .unwrap_or_default(),
+            FilterOperation::Fuzzy { value, max_edits } => value
+                .as_string()
+                .map(|value| {
+                    self.analyze(value)
+                        .iter()
+                        .flat_map(|term| self.fuzzy_candidates(term, *max_edits))
+                        .map(|(candidate, _)| candidate.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Find the terms starting with the last word of a prefix, together with the amount
+    /// of `items` containing each of them. Terms not present in any of the items are skipped.
+    pub(crate) fn suggest(&self, prefix: &str, items: &RoaringBitmap) -> Vec<(String, u64)> {
+        let Some(prefix) = self.analyzer.analyze_prefix(prefix) else {
+            return Vec::new();
+        };
+
+        self.inner
+            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            .take_while(|(term, _)| term.starts_with(prefix.as_str()))
+            .filter_map(|(term, term_positions)| {
+                let count = term_positions
+                    .0
+                    .keys()
+                    .filter(|position| items.contains(**position))
+                    .count() as u64;
+
+                (count > 0).then(|| (term.clone(), count))
+            })
+            .collect()
+    }
 
-                appended_positions.insert(position);
+    /// Match the pattern against the vocabulary of the index, returning the positions
+    /// of the documents containing any matching word.
+    fn matches_pattern(&self, pattern: &Pattern) -> RoaringBitmap {
+        let pattern = pattern.for_terms(&self.analyzer);
+        let mut hits = RoaringBitmap::new();
+
+        for (word, term_positions) in &self.inner {
+            if pattern.is_match(word) {
+                hits.extend(term_positions.0.keys());
             }
+        }
 
-            // Retain only the words that had a match in the current iteration
-            word_consecutive_matches.retain(|position, indices| {
-                appended_positions.contains(position) && !indices.is_empty()
-            });
+        hits
+    }
+
+    /// Find the positions of the words within the document at `position` matching any of
+    /// the given terms. Terms are expected to be already normalized.
+    pub(crate) fn matched_words(&self, terms: &[String], position: u32) -> HashSet<usize> {
+        terms
+            .iter()
+            .filter_map(|term| self.term_positions(term))
+            .flat_map(|term_positions| term_positions.0.get(&position).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    /// Find the terms of the vocabulary close to each term of a text missing from it, by
+    /// edit distance. The allowed distance grows with the length of the term, and terms
+    /// shorter than three characters are not corrected.
+    pub(crate) fn correct(&self, text: &str) -> Vec<Correction> {
+        let mut corrections = Vec::new();
+
+        for term in self.analyze(text) {
+            if self.term_positions(&term).is_some() {
+                continue;
+            }
+
+            let max_edits = match term.chars().count() {
+                0..=2 => continue,
+                3..=5 => 1,
+                _ => 2,
+            };
+
+            for (candidate, distance) in self.close_terms(&term, max_edits) {
+                let Some(term_positions) = self.inner.get(candidate) else {
+                    continue;
+                };
+
+                corrections.push(Correction {
+                    term: term.clone(),
+                    value: candidate.to_string(),
+                    distance: distance as u32,
+                    count: term_positions.0.len() as u64,
+                });
+            }
         }
 
-        RoaringBitmap::from_iter(word_consecutive_matches.keys())
+        corrections
+    }
+
+    /// Score the documents in `items` containing any of the given terms using BM25, adding
+    /// each document score to `scores`. Terms are expected to be already normalized.
+    pub(crate) fn score(
+        &self,
+        terms: &[String],
+        items: &RoaringBitmap,
+        scores: &mut HashMap<u32, f64>,
+    ) {
+        if self.lengths.is_empty() {
+            return;
+        }
+
+        let documents = self.lengths.len() as f64;
+        let average_length = self.total_length as f64 / documents;
+
+        for term in terms {
+            let Some(term_positions) = self.term_positions(term) else {
+                continue;
+            };
+
+            let idf = inverse_document_frequency(documents, term_positions.0.len() as f64);
+
+            for (position, indices) in &term_positions.0 {
+                if !items.contains(*position) {
+                    continue;
+                }
+
+                let term_frequency = indices.len() as f64;
+                let length = self.lengths.get(position).copied().unwrap_or_default() as f64;
+                let normalization = BM25_K1 * (1.0 - BM25_B + BM25_B * length / average_length);
+
+                *scores.entry(*position).or_default() +=
+                    idf * term_frequency * (BM25_K1 + 1.0) / (term_frequency + normalization);
+            }
+        }
+    }
+
+    /// Find the most distinctive terms of the document at `position`, weighting how often
+    /// the document contains each term by how rare the term is among all the documents.
+    /// Terms not contained in any other document are skipped, since they can't be shared.
+    pub(crate) fn distinctive_terms(&self, position: u32, limit: usize) -> Vec<String> {
+        let documents = self.lengths.len() as f64;
+
+        let mut weighted_terms: Vec<(&String, f64)> = self
+            .inner
+            .iter()
+            .filter(|(_, term_positions)| term_positions.0.len() > 1)
+            .filter_map(|(term, term_positions)| {
+                let term_frequency = term_positions.0.get(&position)?.len() as f64;
+                let idf = inverse_document_frequency(documents, term_positions.0.len() as f64);
+
+                Some((term, term_frequency * idf))
+            })
+            .collect();
+
+        weighted_terms.sort_by(|(first, first_weight), (second, second_weight)| {
+            second_weight
+                .total_cmp(first_weight)
+                .then_with(|| first.cmp(second))
+        });
+
+        weighted_terms
+            .into_iter()
+            .take(limit)
+            .map(|(term, _)| term.clone())
+            .collect()
+    }
+
+    /// Score the documents in `items` sharing the most distinctive terms with the document
+    /// at `position` using BM25, adding each document score to `scores`. The document at
+    /// `position` is never scored itself.
+    pub(crate) fn score_similar(
+        &self,
+        position: u32,
+        items: &RoaringBitmap,
+        scores: &mut HashMap<u32, f64>,
+    ) {
+        let terms = self.distinctive_terms(position, MAX_SIMILAR_TERMS);
+
+        let mut items = items.clone();
+        items.remove(position);
+
+        self.score(&terms, &items, scores);
+    }
+
+    /// Split a text into its terms using the analyzer of the index, as they are
+    /// stored in the index.
+    pub(crate) fn analyze(&self, text: &str) -> Vec<String> {
+        self.analyzer.analyze(text)
+    }
+
+    /// Set the synonyms searched together with the terms of the index, replacing any
+    /// previous ones. Synonyms are analyzed as the indexed values, and each of them must
+    /// result in a single term.
+    pub(crate) fn set_synonyms(&mut self, sets: &[SynonymSet]) -> Result<(), IndexError> {
+        let mut synonyms: HashMap<String, Vec<String>> = HashMap::new();
+
+        for set in sets {
+            let terms = self.analyze_synonyms(&set.terms)?;
+            let expansions = if set.synonyms.is_empty() {
+                terms.clone()
+            } else {
+                self.analyze_synonyms(&set.synonyms)?
+            };
+
+            for term in &terms {
+                synonyms.entry(term.clone()).or_default().extend(
+                    expansions
+                        .iter()
+                        .filter(|expansion| *expansion != term)
+                        .cloned(),
+                );
+            }
+        }
+
+        for expansions in synonyms.values_mut() {
+            expansions.sort();
+            expansions.dedup();
+        }
+
+        self.synonyms = synonyms;
+        Ok(())
+    }
+
+    fn analyze_synonyms(&self, synonyms: &[String]) -> Result<Vec<String>, IndexError> {
+        synonyms
+            .iter()
+            .map(|synonym| match self.analyze(synonym).as_slice() {
+                [term] => Ok(term.clone()),
+                _ => Err(IndexError::InvalidSynonym {
+                    synonym: synonym.clone(),
+                }),
+            })
+            .collect()
+    }
+
+    /// Positions of a term in the documents, merged with the positions of its synonyms.
+    fn term_positions(&self, term: &str) -> Option<Cow<'_, TermPositions>> {
+        let Some(synonyms) = self.synonyms.get(term) else {
+            return self.inner.get(term).map(Cow::Borrowed);
+        };
+
+        let mut merged: Option<TermPositions> = None;
+        for term in std::iter::once(term).chain(synonyms.iter().map(String::as_str)) {
+            if let Some(term_positions) = self.inner.get(term) {
+                merged
+                    .get_or_insert_with(Default::default)
+                    .plus(term_positions);
+            }
+        }
+
+        merged.map(Cow::Owned)
     }
 
     pub(crate) fn plus(&mut self, other: &TermIndex) {
+        self.vocabulary.take();
         for (other_word, other_positions) in &other.inner {
             self.inner
                 .entry(other_word.clone())
                 .and_modify(|positions| positions.plus(other_positions))
                 .or_insert_with(|| other_positions.clone());
         }
+        for (position, length) in &other.lengths {
+            self.remove_length(position);
+            self.lengths.insert(*position, *length);
+            self.total_length += u64::from(*length);
+        }
     }
 
     pub(crate) fn minus(&mut self, other: &TermIndex) {
+        self.vocabulary.take();
         for (other_word, other_positions) in &other.inner {
             if let Some(term_positions) = self.inner.get_mut(other_word) {
                 term_positions.minus(other_positions);
@@ -996,25 +2539,35 @@ impl TermIndex {
                 }
             }
         }
+        for position in other.lengths.keys() {
+            self.remove_length(position);
+        }
     }
 
     /// Insert the content as words in the index for a given position
     pub(crate) fn put(&mut self, content: &str, position: u32) {
-        for (term_index, word) in content
-            .split_whitespace()
-            .filter_map(Self::normalize)
-            .enumerate()
-        {
+        self.vocabulary.take();
+
+        let mut length = 0;
+        for (term_index, word) in self.analyze(content).into_iter().enumerate() {
             let matches = self.inner.entry(word).or_default();
             let terms = matches.0.entry(position).or_default();
 
             terms.insert(term_index);
+            length += 1;
+        }
+
+        if length > 0 {
+            *self.lengths.entry(position).or_default() += length;
+            self.total_length += u64::from(length);
         }
     }
 
     /// Remove all the words for a given position. In case the given word has no results anymore,
     /// it will be emptied from the index.
     pub(crate) fn remove_item(&mut self, position: &u32) {
+        self.vocabulary.take();
+        self.remove_length(position);
         self.inner.retain(|_, term_positions| {
             term_positions
                 .0
@@ -1023,23 +2576,227 @@ impl TermIndex {
         })
     }
 
-    /// Normalize a given input word such that only alpha-numeric characters are allowed.
-    /// In case of an empty output string, `None` is returned.
-    fn normalize(word: &str) -> Option<String> {
-        let word = word
-            .chars()
-            .filter(|c| c.is_alphanumeric())
-            .map(|c| c.to_ascii_lowercase())
-            .collect::<String>();
+    /// Remove the length of the document at `position`, keeping the total length in sync.
+    fn remove_length(&mut self, position: &u32) {
+        if let Some(length) = self.lengths.remove(position) {
+            self.total_length -= u64::from(length);
+        }
+    }
+}
+
+/// Find the shortest span between the first and last word of a phrase, where each word
+/// occurs after the previous one. `word_indices` holds the sorted term indices of each
+/// word in the phrase for a single document.
+fn ordered_span(word_indices: &[Vec<usize>]) -> Option<usize> {
+    let (first_indices, rest) = word_indices.split_first()?;
+
+    let mut shortest: Option<usize> = None;
+    'starts: for start in first_indices {
+        // Pick the earliest occurrence of each word after the previous one, which leads
+        // to the shortest span for the given start.
+        let mut previous = *start;
+        for indices in rest {
+            let next = indices.partition_point(|index| *index <= previous);
+            let Some(index) = indices.get(next) else {
+                // Later starts can't find an occurrence either
+                break 'starts;
+            };
+            previous = *index;
+        }
+
+        let span = previous - start;
+        shortest = Some(shortest.map_or(span, |shortest| shortest.min(span)));
+    }
+
+    shortest
+}
+
+/// Find the shortest span containing all the words of a phrase in any order, respecting
+/// the amount of times a word is repeated in the phrase. `word_indices` holds the sorted
+/// term indices of each word in the phrase for a single document.
+fn unordered_span(words: &[String], word_indices: &[Vec<usize>]) -> Option<usize> {
+    // Collect the occurrences of each distinct word together with how many times
+    // it is required by the phrase.
+    let mut distinct = Vec::<(&String, usize)>::new();
+    let mut occurrences = Vec::new();
+    for (word, indices) in words.iter().zip(word_indices) {
+        if let Some(entry) = distinct.iter_mut().find(|(current, _)| *current == word) {
+            entry.1 += 1;
+            continue;
+        }
+
+        let id = distinct.len();
+        distinct.push((word, 1));
+        occurrences.extend(indices.iter().map(|index| (*index, id)));
+    }
+    occurrences.sort_unstable();
+
+    // Slide a window over the occurrences, shrinking it from the left as long as it
+    // still contains every word.
+    let mut counts = vec![0; distinct.len()];
+    let mut satisfied = 0;
+    let mut left = 0;
+    let mut shortest: Option<usize> = None;
+
+    for &(index, id) in &occurrences {
+        counts[id] += 1;
+        if counts[id] == distinct[id].1 {
+            satisfied += 1;
+        }
+
+        while satisfied == distinct.len() {
+            let (left_index, left_id) = occurrences[left];
 
-        if word.is_empty() {
-            None
+            let span = index - left_index;
+            shortest = Some(shortest.map_or(span, |shortest| shortest.min(span)));
+
+            if counts[left_id] == distinct[left_id].1 {
+                satisfied -= 1;
+            }
+            counts[left_id] -= 1;
+            left += 1;
+        }
+    }
+
+    shortest
+}
+
+/// A term of the vocabulary of a term index close to a searched term missing from it.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Correction {
+    /// The searched term, as analyzed.
+    pub(crate) term: String,
+    /// The term of the vocabulary.
+    pub(crate) value: String,
+    /// Edit distance between both terms.
+    pub(crate) distance: u32,
+    /// Amount of items containing the term of the vocabulary.
+    pub(crate) count: u64,
+}
+
+/// A BK-tree of the terms of a vocabulary, to find the terms within an edit distance of a
+/// word without comparing it to all of them. The children of a node are keyed by their
+/// distance to it, so by the triangle inequality only the children whose distance is within
+/// `max_edits` of the distance between the word and the node can be close to the word.
+#[derive(Default, Debug, Clone)]
+struct BkTree {
+    nodes: Vec<BkNode>,
+}
+
+#[derive(Debug, Clone)]
+struct BkNode {
+    term: String,
+    /// Index of the child node at each distance from the term.
+    children: BTreeMap<usize, usize>,
+}
+
+impl BkTree {
+    fn from_iter<'a, I>(terms: I) -> Self
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let mut tree = BkTree::default();
+        for term in terms {
+            tree.insert(term);
+        }
+        tree
+    }
+
+    fn insert(&mut self, term: &str) {
+        let word: Vec<char> = term.chars().collect();
+        let mut current = 0;
+
+        while let Some(node) = self.nodes.get(current) {
+            let Some(distance) = edit_distance(&word, &node.term, usize::MAX) else {
+                return;
+            };
+            if distance == 0 {
+                return;
+            }
+
+            match node.children.get(&distance) {
+                Some(child) => current = *child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes[current].children.insert(distance, child);
+                    break;
+                }
+            }
+        }
+
+        self.nodes.push(BkNode {
+            term: term.to_string(),
+            children: BTreeMap::new(),
+        });
+    }
+
+    /// Terms within `max_edits` of the given word, together with their distance to it.
+    fn find(&self, word: &[char], max_edits: usize) -> Vec<(&str, usize)> {
+        let mut terms = Vec::new();
+        let mut pending = if self.nodes.is_empty() {
+            Vec::new()
         } else {
-            Some(word)
+            vec![0]
+        };
+
+        while let Some(current) = pending.pop() {
+            let node = &self.nodes[current];
+            let Some(distance) = edit_distance(word, &node.term, usize::MAX) else {
+                continue;
+            };
+
+            if distance <= max_edits {
+                terms.push((node.term.as_str(), distance));
+            }
+
+            let closest = distance.saturating_sub(max_edits);
+            let farthest = distance.saturating_add(max_edits);
+            pending.extend(
+                node.children
+                    .range(closest..=farthest)
+                    .map(|(_, child)| *child),
+            );
         }
+
+        terms
     }
 }
 
+/// Compute the Levenshtein distance between `word` and `candidate`, if it's at most
+/// `max_edits`. The distance is computed row by row, stopping as soon as every entry
+/// of a row exceeds `max_edits`, since the distance can only grow from there.
+fn edit_distance(word: &[char], candidate: &str, max_edits: usize) -> Option<usize> {
+    let candidate: Vec<char> = candidate.chars().collect();
+
+    if word.len().abs_diff(candidate.len()) > max_edits {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = (0..=candidate.len()).collect();
+    let mut current = vec![0; candidate.len() + 1];
+
+    for (i, word_char) in word.iter().enumerate() {
+        current[0] = i + 1;
+        let mut row_min = current[0];
+
+        for (j, candidate_char) in candidate.iter().enumerate() {
+            let substitution = previous[j] + usize::from(word_char != candidate_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+
+            row_min = row_min.min(current[j + 1]);
+        }
+
+        if row_min > max_edits {
+            return None;
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    let distance = previous[candidate.len()];
+    (distance <= max_edits).then_some(distance)
+}
+
 #[derive(Clone, Debug)]
 pub enum TypeName {
     String,
@@ -1070,6 +2827,8 @@ pub enum IndexError {
     UnexpectedValue { expected_type: TypeName },
     #[error("Value \"{value}\" is unknown for enum")]
     UnknownEnumValue { value: String },
+    #[error("synonym \"{synonym}\" must consist of a single term")]
+    InvalidSynonym { synonym: String },
 }
 
 #[derive(Error, Debug)]
@@ -1079,6 +2838,8 @@ pub enum FilterError {
     MissingIndex(String),
     #[error("term index is not present for field")]
     MissingTermIndex,
+    #[error("trigram index is not present for field")]
+    MissingTrigramIndex,
     #[error("Invalid filter value for filter \"{filter}\". Expected {type_name} value")]
     InvalidInput {
         filter: FilterName,
@@ -1091,15 +2852,152 @@ pub enum FilterError {
     },
     #[error("Value \"{value}\" is unknown for enum in filter \"{filter}\"")]
     UnknownEnumValue { value: String, filter: FilterName },
+    #[error("Invalid pattern \"{pattern}\"")]
+    InvalidPattern { pattern: String },
+    #[error("only term operations are supported for all fields")]
+    UnsupportedAllFieldsOperation,
+}
+
+/// Layout of the indices stored before their format was versioned. They are only read to
+/// migrate the stored indices to the current layout.
+pub(crate) mod legacy {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    use indexmap::IndexSet;
+    use ordered_float::OrderedFloat;
+    use roaring::RoaringBitmap;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) enum Index {
+        String(StringIndex),
+        Numeric(NumericIndex),
+        Date(DateIndex),
+        Enum(EnumIndex),
+        Bool(BoolIndex),
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct StringIndex {
+        pub(crate) inner: SortableIndex<String>,
+        pub(crate) term: Option<TermIndex>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct NumericIndex {
+        pub(crate) inner: SortableIndex<OrderedFloat<f64>>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct DateIndex {
+        pub(crate) inner: SortableIndex<i64>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct EnumIndex {
+        pub(crate) values: IndexSet<String>,
+        pub(crate) inner: SortableIndex<usize>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct BoolIndex {
+        pub(crate) inner: SortableIndex<bool>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct SortableIndex<T: Ord>(pub(crate) BTreeMap<T, RoaringBitmap>);
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct TermIndex {
+        pub(crate) inner: HashMap<String, TermPositions>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct TermPositions(pub(crate) HashMap<u32, HashSet<usize>>);
+
+    impl From<Index> for super::Index {
+        fn from(index: Index) -> Self {
+            match index {
+                Index::String(index) => {
+                    // The terms are analyzed again from the values, as the stored ones may
+                    // have been analyzed differently than the current analysis does.
+                    let term = index.term.map(|_| {
+                        let mut term = super::TermIndex::new();
+                        for (value, positions) in &index.inner.0 {
+                            for position in positions {
+                                term.put(value, position);
+                            }
+                        }
+                        term
+                    });
+
+                    super::Index::String(super::StringIndex {
+                        inner: index.inner.into(),
+                        term,
+                        trigram: None,
+                    })
+                }
+                Index::Numeric(index) => {
+                    // Whether the values were integers is not recorded, so the index is
+                    // decimal if any of its keys is not a positive whole number.
+                    let decimal = index
+                        .inner
+                        .0
+                        .keys()
+                        .any(|key| key.fract() != 0.0 || key.into_inner() < 0.0);
+
+                    super::Index::Numeric(super::NumericIndex {
+                        inner: index.inner.into(),
+                        decimal,
+                    })
+                }
+                Index::Date(index) => super::Index::Date(super::DateIndex {
+                    inner: index.inner.into(),
+                }),
+                Index::Enum(index) => super::Index::Enum(super::EnumIndex {
+                    values: index.values,
+                    inner: index.inner.into(),
+                }),
+                Index::Bool(index) => super::Index::Bool(super::BoolIndex {
+                    inner: index.inner.into(),
+                }),
+            }
+        }
+    }
+
+    impl<T: Ord + Clone + Default> From<SortableIndex<T>> for super::SortableIndex<T> {
+        fn from(index: SortableIndex<T>) -> Self {
+            let mut sortable = super::SortableIndex::default();
+
+            for (key, positions) in index.0 {
+                for position in &positions {
+                    sortable.put(key.clone(), position);
+                }
+            }
+
+            sortable
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    use heed::types::SerdeBincode;
+    use heed::{BytesDecode, BytesEncode};
+    use ordered_float::OrderedFloat;
     use roaring::RoaringBitmap;
 
-    use crate::index::{Index, NumericIndex};
+    use crate::analysis::{Analyzer, Language, SynonymSet};
+    use crate::data::{date_to_timestamp, parse_date, FieldValue};
+    use crate::index::{
+        legacy, Correction, DateIndex, FilterError, Index, IndexError, NumericIndex, Pattern,
+        StringIndex, StringTypeDescriptor, TypeDescriptor, TypeName,
+    };
+    use crate::query::{FilterName, FilterOperation};
 
-    use super::TermIndex;
+    use super::{edit_distance, BkTree, TermIndex};
 
     #[test]
     fn index_plus() {
@@ -1154,6 +3052,219 @@ mod tests {
         );
     }
 
+    #[test]
+    fn index_tracks_missing_values() {
+        // given
+        let mut index = Index::Numeric(NumericIndex::new());
+        index.put_missing(0);
+        index.put_missing(1);
+        index.put(FieldValue::dec(1.0), 2).unwrap();
+
+        let mut before = Index::Numeric(NumericIndex::new());
+        before.put_missing(1);
+
+        let mut after = Index::Numeric(NumericIndex::new());
+        after.put(FieldValue::dec(2.0), 1).unwrap();
+
+        // when
+        index.minus(&before).unwrap();
+        index.plus(&after).unwrap();
+        index.remove_item(0);
+
+        // then
+        let all = RoaringBitmap::from([0, 1, 2]);
+        assert_eq!(index.count_missing(&all), 0);
+        assert_eq!(
+            index.filter(&FilterOperation::Exists).unwrap(),
+            RoaringBitmap::from([1, 2])
+        );
+        assert_eq!(
+            index.filter(&FilterOperation::IsNull).unwrap(),
+            RoaringBitmap::new()
+        );
+    }
+
+    #[test]
+    fn string_index_between() {
+        // given
+        let index = Index::String(StringIndex::from_iter([
+            ("Alpha".to_string(), RoaringBitmap::from([0])),
+            ("Beta".to_string(), RoaringBitmap::from([1])),
+            ("Gamma".to_string(), RoaringBitmap::from([2])),
+        ]));
+
+        // when
+        let range = index.filter(&FilterOperation::Between(
+            FieldValue::str("B"),
+            FieldValue::str("H"),
+        ));
+        let reversed = index.filter(&FilterOperation::Between(
+            FieldValue::str("H"),
+            FieldValue::str("B"),
+        ));
+
+        // then
+        assert_eq!(range.unwrap(), RoaringBitmap::from([1, 2]));
+        assert_eq!(reversed.unwrap(), RoaringBitmap::new());
+    }
+
+    #[test]
+    fn numeric_index_between() {
+        // given
+        let index = Index::Numeric(NumericIndex::from_iter([
+            (OrderedFloat(1.0), RoaringBitmap::from([0])),
+            (OrderedFloat(5.0), RoaringBitmap::from([1])),
+            (OrderedFloat(10.0), RoaringBitmap::from([2])),
+        ]));
+
+        // when
+        let range = index.filter(&FilterOperation::Between(
+            FieldValue::dec(1.0),
+            FieldValue::dec(5.0),
+        ));
+        let reversed = index.filter(&FilterOperation::Between(
+            FieldValue::dec(10.0),
+            FieldValue::dec(1.0),
+        ));
+        let invalid = index.filter(&FilterOperation::Between(
+            FieldValue::str("a"),
+            FieldValue::str("b"),
+        ));
+
+        // then
+        assert_eq!(range.unwrap(), RoaringBitmap::from([0, 1]));
+        assert_eq!(reversed.unwrap(), RoaringBitmap::new());
+        assert!(matches!(
+            invalid,
+            Err(FilterError::InvalidInput {
+                filter: FilterName::Between,
+                type_name: TypeName::Numeric,
+            })
+        ));
+    }
+
+    #[test]
+    fn numeric_index_get_value() {
+        // given
+        let mut integers = NumericIndex::new();
+        integers.put(FieldValue::int(5), 0).unwrap();
+        integers.put(FieldValue::int(10), 1).unwrap();
+
+        let mut decimals = integers.clone();
+        decimals.put(FieldValue::dec(2.5), 2).unwrap();
+
+        let integers = Index::Numeric(integers);
+        let decimals = Index::Numeric(decimals);
+
+        // when
+        let stored = SerdeBincode::<Index>::bytes_encode(&integers).unwrap();
+        let restored = SerdeBincode::<Index>::bytes_decode(&stored).unwrap();
+
+        // then
+        assert_eq!(restored, integers);
+        assert_eq!(restored.get_value(1), Some(FieldValue::int(10)));
+        assert_eq!(restored.get_value(2), None);
+        assert_eq!(decimals.get_value(1), Some(FieldValue::dec(10.0)));
+        assert_eq!(decimals.get_value(2), Some(FieldValue::dec(2.5)));
+    }
+
+    #[test]
+    fn date_index_between() {
+        // given
+        let timestamp = |date: &str| date_to_timestamp(parse_date(date).unwrap());
+        let index = Index::Date(DateIndex::from_iter([
+            (timestamp("2020-01-01"), RoaringBitmap::from([0])),
+            (timestamp("2022-01-01"), RoaringBitmap::from([1])),
+            (timestamp("2024-01-01"), RoaringBitmap::from([2])),
+        ]));
+
+        // when
+        let range = index.filter(&FilterOperation::Between(
+            FieldValue::str("2021-01-01"),
+            FieldValue::str("2024-01-01"),
+        ));
+        let reversed = index.filter(&FilterOperation::Between(
+            FieldValue::str("2024-01-01"),
+            FieldValue::str("2021-01-01"),
+        ));
+        let invalid = index.filter(&FilterOperation::Between(
+            FieldValue::str("a"),
+            FieldValue::int(1),
+        ));
+
+        // then
+        assert_eq!(range.unwrap(), RoaringBitmap::from([1, 2]));
+        assert_eq!(reversed.unwrap(), RoaringBitmap::new());
+        assert!(matches!(
+            invalid,
+            Err(FilterError::InvalidInput {
+                filter: FilterName::Between,
+                type_name: TypeName::Date,
+            })
+        ));
+    }
+
+    #[test]
+    fn like_pattern() {
+        // given
+        let pattern = Pattern::like("Cris.%o_");
+
+        // then
+        assert_eq!(pattern.prefix, "Cris.");
+        assert!(pattern.is_match("Cris.tiano1"));
+        assert!(pattern.is_match("Cris.o!"));
+        assert!(!pattern.is_match("Crisxtiano1"));
+        assert!(!pattern.is_match("Cris.tiano"));
+    }
+
+    #[test]
+    fn like_pattern_with_escaped_wildcards() {
+        // given
+        let pattern = Pattern::like("100\\%_\\\\%");
+
+        // then
+        assert_eq!(pattern.prefix, "100%");
+        assert!(pattern.is_match("100%a\\"));
+        assert!(pattern.is_match("100%!\\ cotton"));
+        assert!(!pattern.is_match("1000a\\"));
+        assert!(!pattern.is_match("100%a"));
+    }
+
+    #[test]
+    fn string_index_like_terms() {
+        // given
+        let mut index = Index::from_type(&TypeDescriptor::String(StringTypeDescriptor {
+            term: true,
+            analyzer: Analyzer {
+                fold_accents: true,
+                ..Analyzer::default()
+            },
+            trigram: false,
+        }));
+        index.put(FieldValue::str("Lionel Messi"), 0).unwrap();
+        index.put(FieldValue::str("Mesut Özil"), 1).unwrap();
+        index.put(FieldValue::str("100% Özil"), 2).unwrap();
+        index.put(FieldValue::str("1000 fans"), 3).unwrap();
+
+        let like = |pattern: &str| {
+            index
+                .filter(&FilterOperation::Like(FieldValue::str(pattern)))
+                .unwrap()
+        };
+
+        // when
+        let upper = like("MES%");
+        let accents = like("%ÖZ_L");
+        let escaped = like("100\\%%");
+        let wildcard = like("100%");
+
+        // then
+        assert_eq!(upper, RoaringBitmap::from([0, 1]));
+        assert_eq!(accents, RoaringBitmap::from([1, 2]));
+        assert_eq!(escaped, RoaringBitmap::from([2]));
+        assert_eq!(wildcard, RoaringBitmap::from([2, 3]));
+    }
+
     #[test]
     fn term_index_put_ignores_non_alphabetic_chars() {
         // given
@@ -1234,6 +3345,281 @@ mod tests {
         assert_eq!(index.contains("foo"), RoaringBitmap::new());
     }
 
+    #[test]
+    fn term_index_fuzzy() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Cristiano Ronaldo", 0);
+        index.put("Ronald Koeman", 1);
+        index.put("Lionel Messi", 2);
+
+        // when
+        let exact = index.fuzzy("Ronaldo", 0);
+        let one_edit = index.fuzzy("Ronaldoo", 1);
+        let two_edits = index.fuzzy("Ronaldoo", 2);
+
+        // then
+        assert_eq!(exact, RoaringBitmap::from([0]));
+        assert_eq!(one_edit, RoaringBitmap::from([0]));
+        assert_eq!(two_edits, RoaringBitmap::from([0, 1]));
+    }
+
+    #[test]
+    fn term_index_fuzzy_after_put() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Cristiano Ronaldo", 0);
+        let before = index.fuzzy("Messy", 1);
+
+        // when
+        index.put("Lionel Messi", 1);
+        let after = index.fuzzy("Messy", 1);
+
+        // then
+        assert_eq!(before, RoaringBitmap::new());
+        assert_eq!(after, RoaringBitmap::from([1]));
+    }
+
+    #[test]
+    fn term_index_correct() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Cristiano Ronaldo", 0);
+        index.put("Ronald Koeman", 1);
+        index.put("Ronald Reagan", 2);
+        index.put("Lionel Messi", 3);
+
+        let correction = |term: &str, value: &str, count: u64| Correction {
+            term: term.to_string(),
+            value: value.to_string(),
+            distance: 1,
+            count,
+        };
+
+        // when
+        let corrections = index.correct("Ronaldd Mesi xy Lionel");
+
+        // then
+        assert_eq!(
+            corrections,
+            vec![
+                correction("ronaldd", "ronald", 2),
+                correction("ronaldd", "ronaldo", 1),
+                correction("mesi", "messi", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn term_index_synonyms() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Football club", 0);
+        index.put("Soccer club", 1);
+        index.put("Basketball club", 2);
+
+        index
+            .set_synonyms(&[
+                SynonymSet::equivalent(&["football", "Soccer"]),
+                SynonymSet::one_way(&["nba"], &["basketball"]),
+            ])
+            .unwrap();
+
+        // when
+        let equivalent = index.contains("soccer");
+        let phrase = index.match_phrase("football club");
+        let one_way = index.contains("NBA");
+        let reverse = index.contains("basketball");
+        let invalid = index.set_synonyms(&[SynonymSet::equivalent(&["us", "united states"])]);
+
+        // then
+        assert_eq!(equivalent, RoaringBitmap::from([0, 1]));
+        assert_eq!(phrase, RoaringBitmap::from([0, 1]));
+        assert_eq!(one_way, RoaringBitmap::from([2]));
+        assert_eq!(reverse, RoaringBitmap::from([2]));
+        assert!(matches!(
+            invalid,
+            Err(IndexError::InvalidSynonym { synonym }) if synonym == "united states"
+        ));
+    }
+
+    #[test]
+    fn term_index_suggest() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Cristiano Ronaldo", 0);
+        index.put("Ronald Koeman", 1);
+        index.put("Ronaldo Nazário", 2);
+        index.put("Lionel Messi", 3);
+
+        // when
+        let all = index.suggest("Ronal", &RoaringBitmap::from([0, 1, 2, 3]));
+        let within = index.suggest("Lionel Ronal", &RoaringBitmap::from([1, 3]));
+        let missing = index.suggest("", &RoaringBitmap::from([0, 1, 2, 3]));
+
+        // then
+        assert_eq!(
+            all,
+            vec![("ronald".to_string(), 1), ("ronaldo".to_string(), 2)]
+        );
+        assert_eq!(within, vec![("ronald".to_string(), 1)]);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn string_index_highlight() {
+        // given
+        let mut index = Index::from_type(&TypeDescriptor::String(StringTypeDescriptor {
+            term: true,
+            analyzer: Analyzer {
+                language: Some(Language::English),
+                stop_words: true,
+                stemming: true,
+                ..Analyzer::default()
+            },
+            trigram: false,
+        }));
+        index
+            .put(
+                FieldValue::str("The engine's deltas: searching (fast) & searched!"),
+                0,
+            )
+            .unwrap();
+
+        let value = "The engine's deltas: searching (fast) & searched!";
+
+        // when
+        let spans = index.highlight(
+            &FilterOperation::Contains(FieldValue::str("search deltas")),
+            0,
+            value,
+        );
+        let fuzzy = index.highlight(
+            &FilterOperation::Fuzzy {
+                value: FieldValue::str("enjine"),
+                max_edits: 1,
+            },
+            0,
+            value,
+        );
+        let missing = index.highlight(
+            &FilterOperation::Contains(FieldValue::str("delta")),
+            1,
+            value,
+        );
+
+        // then
+        assert_eq!(spans, vec![13..19, 21..30, 40..48]);
+        assert_eq!(fuzzy, vec![4..12]);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn string_index_substring() {
+        // given
+        let mut index = Index::from_type(&TypeDescriptor::String(StringTypeDescriptor {
+            term: false,
+            analyzer: Analyzer::default(),
+            trigram: true,
+        }));
+        index.put(FieldValue::str("ABX12-7"), 0).unwrap();
+        index.put(FieldValue::str("X1"), 1).unwrap();
+        index.put(FieldValue::str("BX1-2X12"), 2).unwrap();
+        index.put(FieldValue::str("ABCx-BCD"), 3).unwrap();
+        index.put(FieldValue::str("ABCD"), 4).unwrap();
+
+        let substring = |value: &str| {
+            index
+                .filter(&FilterOperation::Substring(FieldValue::str(value)))
+                .unwrap()
+        };
+
+        // when
+        let code = substring("X12");
+        let short = substring("X1");
+        let verified = substring("ABCD");
+        let missing = substring("x12");
+
+        // then
+        assert_eq!(code, RoaringBitmap::from([0, 2]));
+        assert_eq!(short, RoaringBitmap::from([0, 1, 2]));
+        assert_eq!(verified, RoaringBitmap::from([4]));
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn string_index_substring_without_trigrams() {
+        // given
+        let index = Index::from_type(&TypeDescriptor::String(StringTypeDescriptor {
+            term: true,
+            analyzer: Analyzer::default(),
+            trigram: false,
+        }));
+
+        // when
+        let result = index.filter(&FilterOperation::Substring(FieldValue::str("X12")));
+
+        // then
+        assert!(matches!(result, Err(FilterError::MissingTrigramIndex)));
+    }
+
+    #[test]
+    fn string_index_suggest_values() {
+        // given
+        let mut index = Index::from_type(&TypeDescriptor::String(StringTypeDescriptor {
+            term: false,
+            analyzer: Analyzer::default(),
+            trigram: false,
+        }));
+        index.put(FieldValue::str("Barcelona"), 0).unwrap();
+        index.put(FieldValue::str("Bayern"), 1).unwrap();
+        index.put(FieldValue::str("Bayern"), 2).unwrap();
+        index.put(FieldValue::str("Chelsea"), 3).unwrap();
+
+        // when
+        let suggestions = index
+            .suggest("Ba", &RoaringBitmap::from([0, 1, 2, 3]))
+            .unwrap();
+
+        // then
+        assert_eq!(
+            suggestions,
+            vec![("Barcelona".to_string(), 1), ("Bayern".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn edit_distance_within_max_edits() {
+        let word: Vec<char> = "kitten".chars().collect();
+
+        assert_eq!(edit_distance(&word, "kitten", 0), Some(0));
+        assert_eq!(edit_distance(&word, "sitting", 3), Some(3));
+        assert_eq!(edit_distance(&word, "sitting", 2), None);
+        assert_eq!(edit_distance(&word, "kiten", 1), Some(1));
+        assert_eq!(edit_distance(&word, "kit", 2), None);
+    }
+
+    #[test]
+    fn bk_tree_find() {
+        // given
+        let terms = [
+            "book", "books", "boo", "cake", "boon", "cook", "cape", "cart",
+        ]
+        .map(String::from);
+        let tree = BkTree::from_iter(&terms);
+        let word: Vec<char> = "bok".chars().collect();
+
+        // when
+        let mut close = tree.find(&word, 1);
+        let mut all = tree.find(&word, 10);
+        close.sort_unstable();
+        all.sort_unstable();
+
+        // then
+        assert_eq!(close, vec![("boo", 1), ("book", 1)]);
+        assert_eq!(all.len(), terms.len());
+    }
+
     #[test]
     fn term_index_match_phrase() {
         // given
@@ -1261,6 +3647,137 @@ mod tests {
         assert_eq!(index.match_phrase("."), RoaringBitmap::from([]));
     }
 
+    #[test]
+    fn string_index_with_analyzer() {
+        // given
+        let analyzer = Analyzer {
+            language: Some(Language::French),
+            fold_accents: true,
+            stop_words: true,
+            stemming: true,
+        };
+        let mut index = Index::from_type(&TypeDescriptor::String(StringTypeDescriptor {
+            term: true,
+            analyzer: analyzer.clone(),
+            trigram: false,
+        }));
+
+        index
+            .put(FieldValue::str("Une École des Beaux-Arts"), 0)
+            .unwrap();
+        index
+            .put(FieldValue::str("Les écoles de Paris"), 1)
+            .unwrap();
+
+        // when
+        let contains = index.filter(&FilterOperation::Contains(FieldValue::str("ECOLE")));
+        let phrase = index.filter(&FilterOperation::Matches(FieldValue::str(
+            "écoles de paris",
+        )));
+        let descriptor = index.create_descriptor();
+
+        // then
+        assert_eq!(contains.unwrap(), RoaringBitmap::from([0, 1]));
+        assert_eq!(phrase.unwrap(), RoaringBitmap::from([1]));
+        assert!(matches!(
+            descriptor,
+            TypeDescriptor::String(StringTypeDescriptor { term: true, analyzer: descriptor_analyzer, .. })
+                if descriptor_analyzer == analyzer
+        ));
+    }
+
+    #[test]
+    fn term_index_match_near() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Delta storage for search", 1);
+        index.put("Storage of deltas in a very fast delta engine", 2);
+        index.put("A delta is applied on top of the storage", 3);
+        index.put("Delta delta storage", 4);
+
+        // when
+        let adjacent = index.match_near("delta storage", 0, false);
+        let unordered = index.match_near("delta storage", 2, false);
+        let ordered = index.match_near("delta storage", 2, true);
+        let wide = index.match_near("delta storage", 6, true);
+        let repeated = index.match_near("delta delta storage", 0, false);
+
+        // then
+        assert_eq!(adjacent, RoaringBitmap::from([1, 4]));
+        assert_eq!(unordered, RoaringBitmap::from([1, 4]));
+        assert_eq!(ordered, RoaringBitmap::from([1, 4]));
+        assert_eq!(wide, RoaringBitmap::from([1, 3, 4]));
+        assert_eq!(repeated, RoaringBitmap::from([4]));
+        assert_eq!(
+            index.match_near("engine storage", 8, false),
+            RoaringBitmap::from([2])
+        );
+        assert_eq!(
+            index.match_near("engine storage", 8, true),
+            RoaringBitmap::from([])
+        );
+        assert_eq!(
+            index.match_near("delta missing", 10, false),
+            RoaringBitmap::new()
+        );
+    }
+
+    #[test]
+    fn term_index_score() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Delta search engine", 1);
+        index.put("Delta delta storage", 2);
+        index.put("Search", 3);
+
+        let items = RoaringBitmap::from([1, 2, 3]);
+        let score = |terms: &[&str], items: &RoaringBitmap| {
+            let terms: Vec<String> = terms.iter().map(|term| term.to_string()).collect();
+            let mut scores = HashMap::new();
+            index.score(&terms, items, &mut scores);
+            scores
+        };
+
+        // when
+        let delta = score(&["delta"], &items);
+        let storage = score(&["storage"], &items);
+        let combined = score(&["delta", "storage"], &items);
+        let filtered = score(&["delta"], &RoaringBitmap::from([1]));
+
+        // then
+        assert_eq!(delta.len(), 2);
+        assert!(delta[&2] > delta[&1]);
+        assert!(storage[&2] > delta[&2]);
+        assert!((combined[&2] - (delta[&2] + storage[&2])).abs() < f64::EPSILON);
+        assert_eq!(filtered.keys().collect::<Vec<_>>(), vec![&1]);
+        assert!(score(&["missing"], &items).is_empty());
+    }
+
+    #[test]
+    fn term_index_similar() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Delta search engine", 1);
+        index.put("Delta delta storage", 2);
+        index.put("Search", 3);
+        index.put("Delta search storage unique", 4);
+
+        let mut scores = HashMap::new();
+
+        // when
+        let terms = index.distinctive_terms(4, 10);
+        let limited = index.distinctive_terms(4, 2);
+        index.score_similar(4, &RoaringBitmap::from([1, 2, 3, 4]), &mut scores);
+
+        // then
+        assert_eq!(terms, vec!["storage", "delta", "search"]);
+        assert_eq!(limited, vec!["storage", "delta"]);
+        assert_eq!(scores.len(), 3);
+        assert!(!scores.contains_key(&4));
+        assert!(scores[&2] > scores[&1]);
+        assert!(scores[&2] > scores[&3]);
+    }
+
     #[test]
     fn term_index_remove() {
         // given
@@ -1286,6 +3803,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn term_index_total_length() {
+        // given
+        let mut index = TermIndex::new();
+        index.put("Delta search engine", 1);
+        index.put("Delta storage", 2);
+
+        let mut before = TermIndex::new();
+        before.put("Delta storage", 2);
+
+        let mut after = TermIndex::new();
+        after.put("Delta storage on disk", 2);
+
+        // when
+        index.minus(&before);
+        index.plus(&after);
+        let updated = index.total_length;
+        index.remove_item(&1);
+
+        // then
+        assert_eq!(updated, 7);
+        assert_eq!(index.total_length, 4);
+    }
+
     #[test]
     fn term_index_plus_partial() {
         // given
@@ -1412,4 +3953,36 @@ mod tests {
         // then
         assert_eq!(index, TermIndex::new());
     }
+
+    #[test]
+    fn string_index_from_legacy() {
+        // given
+        let legacy = legacy::Index::String(legacy::StringIndex {
+            inner: legacy::SortableIndex(BTreeMap::from([
+                ("École Centrale".to_string(), RoaringBitmap::from([1])),
+                ("Delta search".to_string(), RoaringBitmap::from([2])),
+            ])),
+            // Terms analyzed differently than the current analysis, so they are not kept
+            term: Some(legacy::TermIndex {
+                inner: HashMap::from([(
+                    "École".to_string(),
+                    legacy::TermPositions(HashMap::from([(1, HashSet::from([0]))])),
+                )]),
+            }),
+        });
+
+        // when
+        let migrated = Index::from(legacy);
+
+        // then
+        let mut expected = Index::from_type(&TypeDescriptor::String(StringTypeDescriptor {
+            term: true,
+            analyzer: Analyzer::default(),
+            trigram: false,
+        }));
+        expected.put(FieldValue::str("École Centrale"), 1).unwrap();
+        expected.put(FieldValue::str("Delta search"), 2).unwrap();
+
+        assert_eq!(migrated, expected);
+    }
 }
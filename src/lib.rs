@@ -1,4 +1,5 @@
 use papaya::HashMap;
+use std::collections::BTreeMap;
 use std::slice;
 
 use thiserror::Error;
@@ -6,10 +7,15 @@ use thiserror::Error;
 use query::{DeltaScope, QueryError};
 use storage::StorageError;
 
+use crate::analysis::SynonymSet;
 use crate::data::{DataItem, DataItemId};
-use crate::query::{DeltaChange, FilterOption, OptionsQueryExecution, QueryExecution};
+use crate::query::{
+    DeltaChange, FilterOption, OptionsQueryExecution, QueryExecution, QueryPlan, QueryResult,
+    SimilarExecution, SimilarItem, SuggestExecution, Suggestion,
+};
 use crate::storage::{CreateFieldIndex, EntityStorage, StorageBuilder};
 
+pub mod analysis;
 pub mod data;
 #[cfg(feature = "test-fixtures")]
 pub mod fixtures;
@@ -52,14 +58,24 @@ impl Engine {
         Ok(())
     }
 
-    pub fn query(&self, execution: QueryExecution) -> Result<Vec<DataItem>, EngineError> {
-        let items = if let Some(entity) = self.entities.pin().get(&execution.entity) {
+    pub fn query(&self, execution: QueryExecution) -> Result<QueryResult, EngineError> {
+        let result = if let Some(entity) = self.entities.pin().get(&execution.entity) {
             execution.run(entity)?
         } else {
-            Vec::new()
+            QueryResult::empty()
         };
 
-        Ok(items)
+        Ok(result)
+    }
+
+    pub fn explain(&self, execution: QueryExecution) -> Result<QueryPlan, EngineError> {
+        let plan = if let Some(entity) = self.entities.pin().get(&execution.entity) {
+            execution.explain(entity)?
+        } else {
+            QueryPlan::empty(execution.entity)
+        };
+
+        Ok(plan)
     }
 
     pub fn options(
@@ -74,6 +90,24 @@ impl Engine {
         Ok(options)
     }
 
+    pub fn suggest(&self, execution: SuggestExecution) -> Result<Vec<Suggestion>, EngineError> {
+        let suggestions = if let Some(entity) = self.entities.pin().get(&execution.target.entity) {
+            execution.run(entity)?
+        } else {
+            Vec::new()
+        };
+        Ok(suggestions)
+    }
+
+    pub fn similar(&self, execution: SimilarExecution) -> Result<Vec<SimilarItem>, EngineError> {
+        let items = if let Some(entity) = self.entities.pin().get(&execution.target.entity) {
+            execution.run(entity)?
+        } else {
+            Vec::new()
+        };
+        Ok(items)
+    }
+
     pub fn add(&self, name: &str, item: &DataItem) -> Result<(), EngineError> {
         self.add_multiple(name, slice::from_ref(item))
     }
@@ -111,6 +145,31 @@ impl Engine {
         Ok(())
     }
 
+    /// Replace the synonym sets searched together with the terms of a field.
+    pub fn store_synonyms(
+        &self,
+        name: &str,
+        field: &str,
+        sets: Vec<SynonymSet>,
+    ) -> Result<(), EngineError> {
+        if let Some(entity) = self.entities.pin().get(name) {
+            entity.store_synonyms(field, sets)?;
+
+            Ok(())
+        } else {
+            Err(EngineError::EntityNotFound)
+        }
+    }
+
+    /// Read the synonym sets of every field of an entity.
+    pub fn synonyms(&self, name: &str) -> Result<BTreeMap<String, Vec<SynonymSet>>, EngineError> {
+        if let Some(entity) = self.entities.pin().get(name) {
+            Ok(entity.read_synonyms()?)
+        } else {
+            Err(EngineError::EntityNotFound)
+        }
+    }
+
     pub fn create_index(&self, name: &str, command: CreateFieldIndex) -> Result<(), EngineError> {
         if let Some(entity) = self.entities.pin().get(name) {
             entity.create_indices(vec![command])?;
@@ -143,18 +202,25 @@ mod tests {
     use lazy_static::lazy_static;
     use time::{Date, Month};
 
-    use crate::data::{DataItem, FieldValue};
+    use crate::analysis::SynonymSet;
+    use crate::data::{DataItem, DataItemId, FieldValue};
     use crate::fixtures::{
         create_player_from_index, create_random_players, cristiano_ronaldo, david, lionel_messi,
-        michael_jordan, roger, DecreaseScoreDelta, Player, Sport, SwitchSportsDelta, TestRunners,
+        michael_jordan, roger, string_index, with_string_field, DecreaseScoreDelta, Player, Sport,
+        SwitchSportsDelta, TestRunners,
     };
+    use crate::index::TypeDescriptor;
     use crate::query::{
-        CompositeFilter, DeltaChange, DeltaScope, FilterOption, OptionsQueryExecution, Pagination,
-        QueryExecution, Sort, SortDirection,
+        AggregateFunction, Aggregation, AggregationGroup, AggregationValue, Boost, CompositeFilter,
+        DeltaChange, DeltaPlan, DeltaScope, FilterNode, FilterOption, Highlight, HighlightSpan,
+        NullsPlacement, OptionsQueryExecution, Pagination, QueryExecution, SimilarExecution, Sort,
+        SortDirection, SortStrategy, SpellingSuggestion, SuggestExecution, Suggestion, ALL_FIELDS,
+        SCORE_FIELD,
     };
+    use crate::storage::CreateFieldIndex;
 
     lazy_static! {
-        static ref STORAGES: TestRunners = TestRunners::start(24);
+        static ref STORAGES: TestRunners = TestRunners::start(55);
         static ref MICHAEL_JORDAN: DataItem = michael_jordan();
         static ref LIONEL_MESSI: DataItem = lionel_messi();
         static ref CRISTIANO_RONALDO: DataItem = cristiano_ronaldo();
@@ -182,7 +248,8 @@ mod tests {
                     .for_entity(runner.name.clone())
                     .with_filter(filter),
             )
-            .unwrap();
+            .unwrap()
+            .items;
 
         // then
         matches.sort_by(|a, b| a.id.cmp(&b.id));
@@ -209,7 +276,8 @@ mod tests {
                     .for_entity(runner.name.clone())
                     .with_filter(filter),
             )
-            .unwrap();
+            .unwrap()
+            .items;
 
         // then
         matches.sort_by(|a, b| a.id.cmp(&b.id));
@@ -218,64 +286,65 @@ mod tests {
     }
 
     #[test]
-    fn query_enum_eq_filter() {
+    fn query_string_starts_with_filter() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
+            DAVID.clone(),
         ]);
 
-        let filter = CompositeFilter::eq("sport", FieldValue::str("Football"));
+        let filter = CompositeFilter::starts_with("name", FieldValue::str("Mic"));
 
         // when
-        let mut matches = runner
+        let matches = runner
             .engine
             .query(
                 QueryExecution::new()
                     .for_entity(runner.name.clone())
                     .with_filter(filter),
             )
-            .unwrap();
+            .unwrap()
+            .items;
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
-
-        assert_eq!(
-            matches,
-            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone()]
-        );
+        assert_eq!(matches, vec![MICHAEL_JORDAN.clone()]);
     }
 
     #[test]
-    fn query_bool_eq_filter() {
+    fn query_string_between_filter() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
+            DAVID.clone(),
         ]);
 
-        let filter = CompositeFilter::eq("active", FieldValue::bool(false));
+        let execution = QueryExecution::parse_query(&format!(
+            "FROM {} WHERE name BETWEEN \"C\" AND \"M\"",
+            runner.name
+        ))
+        .unwrap();
 
         // when
-        let mut matches = runner
-            .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_filter(filter),
-            )
-            .unwrap();
+        let mut matches = runner.engine.query(execution).unwrap().items;
 
         // then
         matches.sort_by(|a, b| a.id.cmp(&b.id));
-
-        assert_eq!(matches, vec![MICHAEL_JORDAN.clone()]);
+        assert_eq!(
+            matches,
+            vec![
+                LIONEL_MESSI.clone(),
+                CRISTIANO_RONALDO.clone(),
+                DAVID.clone()
+            ]
+        );
     }
 
     #[test]
-    fn query_date_ge_filter() {
+    fn query_string_pattern_filters() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
@@ -284,26 +353,44 @@ mod tests {
             ROGER.clone(),
         ]);
 
-        let filter = CompositeFilter::ge("birth_date", FieldValue::str("1990-01-01"));
+        let query = |filter: &str| {
+            let execution =
+                QueryExecution::parse_query(&format!("FROM {} WHERE {}", runner.name, filter))
+                    .unwrap();
+
+            let mut ids: Vec<DataItemId> = runner
+                .engine
+                .query(execution)
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.id)
+                .collect();
+
+            ids.sort();
+            ids
+        };
 
         // when
-        let mut matches = runner
-            .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_filter(filter),
-            )
-            .unwrap();
+        let like = query("name LIKE \"Cris%o\"");
+        let like_term = query("name LIKE \"mes_i\"");
+        let like_upper_term = query("name LIKE \"MES%\"");
+        let like_escaped = query("name LIKE \"Cris\\\\%\"");
+        let regex = query("name REGEX \"^[A-M].*\"");
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
-
-        assert_eq!(matches, vec![ROGER.clone()]);
+        assert_eq!(like, vec![CRISTIANO_RONALDO.id]);
+        assert_eq!(like_term, vec![LIONEL_MESSI.id]);
+        assert_eq!(like_upper_term, vec![LIONEL_MESSI.id]);
+        assert!(like_escaped.is_empty());
+        assert_eq!(
+            regex,
+            vec![MICHAEL_JORDAN.id, LIONEL_MESSI.id, CRISTIANO_RONALDO.id]
+        );
     }
 
     #[test]
-    fn query_date_between_filter() {
+    fn query_string_term_fuzzy_filter() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
@@ -312,284 +399,1509 @@ mod tests {
             ROGER.clone(),
         ]);
 
-        let filter = CompositeFilter::between(
-            "birth_date",
-            FieldValue::str("1970-01-01"),
-            FieldValue::str("1990-01-01"),
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
         );
+        let deltas = vec![DeltaChange::new(
+            ROGER.id,
+            "name".to_string(),
+            FieldValue::str("Rogerio Ceni"),
+        )];
 
-        // when
-        let mut matches = runner
+        runner
             .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_filter(filter),
-            )
+            .store_deltas(&runner.name, &delta_scope, deltas)
             .unwrap();
 
-        // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
+        let fuzzy = |value: &str| {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::fuzzy("name", FieldValue::str(value), 1))
+        };
+
+        // when
+        let typo = runner.engine.query(fuzzy("Ronaldoo")).unwrap().items;
+        let current = runner.engine.query(fuzzy("Rogerioo")).unwrap().items;
+        let branch = runner
+            .engine
+            .query(fuzzy("Rogerioo").with_scope(DeltaScope::branch(
+                0,
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            )))
+            .unwrap()
+            .items;
 
+        // then
+        assert_eq!(typo, vec![CRISTIANO_RONALDO.clone()]);
+        assert!(current.is_empty());
         assert_eq!(
-            matches,
-            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone()]
+            branch.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![ROGER.id]
         );
     }
 
     #[test]
-    fn query_numeric_between_filter() {
+    fn query_string_term_near_filter() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
             ROGER.clone(),
         ]);
 
-        let filter = CompositeFilter::between("score", FieldValue::dec(6.0), FieldValue::dec(10.0));
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DeltaChange::new(
+            ROGER.id,
+            "name".to_string(),
+            FieldValue::str("Cristiano Roger Ronaldo"),
+        )];
 
-        // when
-        let mut matches = runner
+        runner
             .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_filter(filter),
-            )
+            .store_deltas(&runner.name, &delta_scope, deltas)
             .unwrap();
 
-        // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
+        let near = |value: &str, slop: u32, ordered: bool| {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::near(
+                    "name",
+                    FieldValue::str(value),
+                    slop,
+                    ordered,
+                ))
+        };
+        let ids = |items: Vec<DataItem>| items.iter().map(|item| item.id).collect::<Vec<_>>();
+        let branch_scope = || {
+            DeltaScope::branch(
+                0,
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            )
+        };
 
-        assert_eq!(matches, vec![MICHAEL_JORDAN.clone(), LIONEL_MESSI.clone()]);
+        // when
+        let unordered = runner.engine.query(near("Ronaldo Cristiano", 0, false));
+        let ordered = runner.engine.query(near("Ronaldo Cristiano", 0, true));
+        let branch_adjacent = runner
+            .engine
+            .query(near("Cristiano Ronaldo", 0, true).with_scope(branch_scope()));
+        let branch_window = runner
+            .engine
+            .query(near("Cristiano Ronaldo", 1, true).with_scope(branch_scope()));
+
+        // then
+        assert_eq!(ids(unordered.unwrap().items), vec![CRISTIANO_RONALDO.id]);
+        assert!(ordered.unwrap().items.is_empty());
+        assert_eq!(
+            ids(branch_adjacent.unwrap().items),
+            vec![CRISTIANO_RONALDO.id]
+        );
+        assert_eq!(
+            ids(branch_window.unwrap().items),
+            vec![CRISTIANO_RONALDO.id, ROGER.id]
+        );
     }
 
     #[test]
-    fn query_numeric_ge_filter() {
+    fn query_term_score_sort() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
             ROGER.clone(),
         ]);
 
-        let filter = CompositeFilter::ge("score", FieldValue::dec(6.0));
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DeltaChange::new(
+            ROGER.id,
+            "name".to_string(),
+            FieldValue::str("Roger Lionel Messi"),
+        )];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        let search = || {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::or(vec![
+                    CompositeFilter::contains("name", FieldValue::str("roger")),
+                    CompositeFilter::matches("name", FieldValue::str("lionel messi")),
+                ]))
+                .with_sort(Sort::new(SCORE_FIELD).with_direction(SortDirection::ASC))
+        };
 
         // when
-        let mut matches = runner
+        let current = runner.engine.query(search()).unwrap();
+        let branch = runner
+            .engine
+            .query(search().with_scope(DeltaScope::branch(
+                0,
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            )))
+            .unwrap();
+        let unranked = runner
             .engine
             .query(
                 QueryExecution::new()
                     .for_entity(runner.name.clone())
-                    .with_filter(filter),
+                    .with_filter(CompositeFilter::eq("sport", FieldValue::str("Football"))),
             )
             .unwrap();
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(current.items, vec![ROGER.clone(), LIONEL_MESSI.clone()]);
+        let scores = current.scores.unwrap();
+        assert!(scores[0] > 0.0 && scores[0] < scores[1]);
 
-        assert_eq!(matches, vec![MICHAEL_JORDAN.clone(), LIONEL_MESSI.clone()]);
+        assert_eq!(
+            branch.items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![LIONEL_MESSI.id, ROGER.id]
+        );
+        let scores = branch.scores.unwrap();
+        assert!(scores[0] > 0.0 && scores[0] < scores[1]);
+
+        assert_eq!(unranked.scores, None);
     }
 
     #[test]
-    fn query_numeric_le_filter() {
+    fn query_substring_filter() {
         // given
-        let runner = STORAGES.start_runner(vec![
-            MICHAEL_JORDAN.clone(),
-            LIONEL_MESSI.clone(),
-            ROGER.clone(),
-        ]);
+        let jordan = with_string_field(&MICHAEL_JORDAN, "code", "ABX12-7");
+        let messi = with_string_field(&LIONEL_MESSI, "code", "CDX13-1");
+        let ronaldo = with_string_field(&CRISTIANO_RONALDO, "code", "X12");
+        let runner = STORAGES.start_runner(vec![jordan.clone(), messi.clone(), ronaldo.clone()]);
 
-        let filter = CompositeFilter::le("score", FieldValue::dec(6.0));
+        runner
+            .engine
+            .create_index(&runner.name, string_index("code", false, true))
+            .unwrap();
 
-        // when
-        let mut matches = runner
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![
+            DeltaChange::new(messi.id, "code".to_string(), FieldValue::str("CDX12-1")),
+            DeltaChange::new(ronaldo.id, "code".to_string(), FieldValue::str("Y21")),
+        ];
+
+        runner
             .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_filter(filter),
-            )
+            .store_deltas(&runner.name, &delta_scope, deltas)
             .unwrap();
 
-        // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
+        let substring = |value: &str| {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::substring("code", FieldValue::str(value)))
+        };
 
-        assert_eq!(matches, vec![ROGER.clone()]);
+        // when
+        let current = runner.engine.query(substring("X12")).unwrap().items;
+        let branch = runner
+            .engine
+            .query(substring("X12").with_scope(DeltaScope::branch(
+                0,
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            )))
+            .unwrap()
+            .items;
+
+        // then
+        assert_eq!(current, vec![jordan.clone(), ronaldo]);
+        assert_eq!(
+            branch.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![jordan.id, messi.id]
+        );
     }
 
     #[test]
-    fn query_and_filter() {
+    fn query_synonyms() {
         // given
+        let jordan = with_string_field(&MICHAEL_JORDAN, "description", "Basketball legend");
+        let messi = with_string_field(&LIONEL_MESSI, "description", "Football player");
+        let ronaldo = with_string_field(&CRISTIANO_RONALDO, "description", "Soccer player");
+        let roger = with_string_field(&ROGER, "description", "NBA referee");
         let runner = STORAGES.start_runner(vec![
-            MICHAEL_JORDAN.clone(),
-            LIONEL_MESSI.clone(),
-            CRISTIANO_RONALDO.clone(),
-            ROGER.clone(),
+            jordan.clone(),
+            messi.clone(),
+            ronaldo.clone(),
+            roger.clone(),
         ]);
 
-        let filter = CompositeFilter::and(vec![
-            CompositeFilter::ge("score", FieldValue::dec(2.0)),
-            CompositeFilter::eq("active", FieldValue::Bool(false)),
-        ]);
+        runner
+            .engine
+            .create_index(&runner.name, string_index("description", true, false))
+            .unwrap();
+
+        let synonyms = vec![
+            SynonymSet::equivalent(&["Football", "soccer"]),
+            SynonymSet::one_way(&["NBA"], &["basketball"]),
+        ];
+        runner
+            .engine
+            .store_synonyms(&runner.name, "description", synonyms.clone())
+            .unwrap();
+
+        let search = |value: &str| {
+            let execution = QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::contains(
+                    "description",
+                    FieldValue::str(value),
+                ));
+            runner.engine.query(execution).unwrap().items
+        };
 
         // when
-        let mut matches = runner
+        let soccer = search("soccer");
+        let nba = search("nba");
+        let basketball = search("basketball");
+        let stored = runner.engine.synonyms(&runner.name).unwrap();
+        let invalid = runner.engine.store_synonyms(
+            &runner.name,
+            "description",
+            vec![SynonymSet::equivalent(&["usa", "united states"])],
+        );
+        let missing_index = runner.engine.store_synonyms(&runner.name, "team", synonyms);
+
+        runner
             .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_filter(filter),
-            )
+            .store_synonyms(&runner.name, "description", Vec::new())
             .unwrap();
+        let removed = search("soccer");
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
-
-        assert_eq!(matches, vec![MICHAEL_JORDAN.clone(), ROGER.clone()]);
+        assert_eq!(soccer, vec![messi, ronaldo.clone()]);
+        assert_eq!(nba, vec![jordan.clone(), roger]);
+        assert_eq!(basketball, vec![jordan]);
+        assert_eq!(
+            stored,
+            BTreeMap::from([(
+                "description".to_string(),
+                vec![
+                    SynonymSet::equivalent(&["Football", "soccer"]),
+                    SynonymSet::one_way(&["NBA"], &["basketball"]),
+                ]
+            )])
+        );
+        assert!(invalid.is_err());
+        assert!(missing_index.is_err());
+        assert_eq!(removed, vec![ronaldo]);
     }
 
     #[test]
-    fn query_or_filter() {
+    fn query_highlight() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
-            ROGER.clone(),
         ]);
 
-        let filter = CompositeFilter::or(vec![
-            CompositeFilter::ge("score", FieldValue::dec(9.0)),
-            CompositeFilter::le("birth_date", FieldValue::str("1990-01-01")),
-        ]);
+        let execution = QueryExecution::new()
+            .for_entity(runner.name.clone())
+            .with_filter(CompositeFilter::or(vec![
+                CompositeFilter::contains("name", FieldValue::str("messi")),
+                CompositeFilter::contains("name", FieldValue::str("cristiano")),
+            ]))
+            .with_highlight("name");
 
         // when
-        let mut matches = runner
-            .engine
+        let result = runner.engine.query(execution).unwrap();
+
+        // then
+        assert_eq!(
+            result.items,
+            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone()]
+        );
+        assert_eq!(
+            result.highlights,
+            Some(vec![
+                BTreeMap::from([(
+                    "name".to_string(),
+                    Highlight {
+                        spans: vec![HighlightSpan { start: 7, end: 12 }],
+                        snippet: "Lionel <em>Messi</em>".to_string(),
+                    }
+                )]),
+                BTreeMap::from([(
+                    "name".to_string(),
+                    Highlight {
+                        spans: vec![HighlightSpan { start: 0, end: 9 }],
+                        snippet: "<em>Cristiano</em> Ronaldo".to_string(),
+                    }
+                )]),
+            ])
+        );
+    }
+
+    #[test]
+    fn query_spelling_suggestions() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let contains = |value: &str| {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::contains("name", FieldValue::str(value)))
+        };
+        let suggestion = |term: &str, suggestion: &str| SpellingSuggestion {
+            field: "name".to_string(),
+            term: term.to_string(),
+            suggestion: suggestion.to_string(),
+            distance: 1,
+            count: 1,
+        };
+
+        // when
+        let typos = runner.engine.query(contains("Ronaldoo Mesi")).unwrap();
+        let known_terms = runner.engine.query(contains("Lionel Ronaldo")).unwrap();
+        let matched = runner.engine.query(contains("Messi")).unwrap();
+
+        // then
+        assert_eq!(typos.total, 0);
+        assert_eq!(
+            typos.suggestions,
+            vec![
+                suggestion("mesi", "messi"),
+                suggestion("ronaldoo", "ronaldo")
+            ]
+        );
+        assert_eq!(known_terms.total, 0);
+        assert!(known_terms.suggestions.is_empty());
+        assert_eq!(matched.total, 1);
+        assert!(matched.suggestions.is_empty());
+    }
+
+    #[test]
+    fn query_suggest() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DeltaChange::new(
+            ROGER.id,
+            "name".to_string(),
+            FieldValue::str("Ronaldo Rogerio"),
+        )];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        let suggest = |prefix: &str| SuggestExecution::new(runner.name.clone(), "name", prefix, 10);
+
+        // when
+        let current = runner.engine.suggest(suggest("Ro")).unwrap();
+        let limited = runner.engine.suggest(suggest("Ro").with_limit(1)).unwrap();
+        let filtered = runner
+            .engine
+            .suggest(
+                suggest("Michael M")
+                    .with_filter(CompositeFilter::eq("sport", FieldValue::str("Football"))),
+            )
+            .unwrap();
+        let branch = runner
+            .engine
+            .suggest(suggest("RO").with_scope(DeltaScope::branch(
+                0,
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            )))
+            .unwrap();
+
+        // then
+        assert_eq!(
+            current,
+            vec![
+                Suggestion::new("roger".to_string(), 1),
+                Suggestion::new("ronaldo".to_string(), 1)
+            ]
+        );
+        assert_eq!(limited, vec![Suggestion::new("roger".to_string(), 1)]);
+        assert_eq!(filtered, vec![Suggestion::new("messi".to_string(), 1)]);
+        assert_eq!(
+            branch,
+            vec![
+                Suggestion::new("ronaldo".to_string(), 2),
+                Suggestion::new("rogerio".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn query_similar() {
+        // given
+        let jordan = with_string_field(
+            &MICHAEL_JORDAN,
+            "description",
+            "Bulls guard scoring champion",
+        );
+        let messi = with_string_field(
+            &LIONEL_MESSI,
+            "description",
+            "Barcelona forward dribbling champion",
+        );
+        let ronaldo = with_string_field(
+            &CRISTIANO_RONALDO,
+            "description",
+            "Madrid forward scoring champion",
+        );
+        let roger = with_string_field(&ROGER, "description", "Barcelona forward");
+        let david = with_string_field(&DAVID, "description", "Bulls guard");
+        let runner = STORAGES.start_runner(vec![
+            jordan.clone(),
+            messi.clone(),
+            ronaldo.clone(),
+            roger.clone(),
+            david.clone(),
+        ]);
+
+        runner
+            .engine
+            .create_index(&runner.name, string_index("description", true, false))
+            .unwrap();
+
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DeltaChange::new(
+            david.id,
+            "description".to_string(),
+            FieldValue::str("Barcelona forward dribbling"),
+        )];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        let fields = vec!["description".to_string()];
+        let similar = |id: DataItemId| SimilarExecution::new(runner.name.clone(), id, &fields, 10);
+        let similar_ids = |execution: SimilarExecution| {
+            runner
+                .engine
+                .similar(execution)
+                .unwrap()
+                .into_iter()
+                .map(|similar| similar.item.id)
+                .collect::<Vec<_>>()
+        };
+
+        // when
+        let current = runner.engine.similar(similar(messi.id)).unwrap();
+        let all_fields = similar_ids(SimilarExecution::new(
+            runner.name.clone(),
+            messi.id,
+            &[],
+            10,
+        ));
+        let limited = similar_ids(similar(messi.id).with_limit(1));
+        let filtered = similar_ids(
+            similar(messi.id)
+                .with_filter(CompositeFilter::eq("sport", FieldValue::str("Basketball"))),
+        );
+        let branch = similar_ids(similar(messi.id).with_scope(DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+        )));
+        let missing_item = similar_ids(similar(42));
+        let missing_field = runner.engine.similar(SimilarExecution::new(
+            runner.name.clone(),
+            messi.id,
+            &["team".to_string()],
+            10,
+        ));
+
+        // then
+        assert_eq!(
+            current
+                .iter()
+                .map(|similar| &similar.item)
+                .collect::<Vec<_>>(),
+            vec![&roger, &ronaldo, &jordan]
+        );
+        assert!(current
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+        assert_eq!(all_fields, vec![roger.id, ronaldo.id, jordan.id]);
+        assert_eq!(limited, vec![roger.id]);
+        assert_eq!(filtered, vec![jordan.id]);
+        assert_eq!(branch[0], david.id);
+        assert!(missing_item.is_empty());
+        assert!(missing_field.is_err());
+    }
+
+    #[test]
+    fn query_all_fields_term_filter() {
+        // given
+        let jordan = with_string_field(&MICHAEL_JORDAN, "team", "Roger Bulls");
+        let messi = with_string_field(&LIONEL_MESSI, "team", "Inter Miami");
+        let runner = STORAGES.start_runner(vec![jordan.clone(), messi.clone(), ROGER.clone()]);
+
+        runner
+            .engine
+            .create_index(&runner.name, string_index("team", true, false))
+            .unwrap();
+
+        let search = |value: &str| {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::contains(
+                    ALL_FIELDS,
+                    FieldValue::str(value),
+                ))
+                .with_sort(Sort::new(SCORE_FIELD).with_direction(SortDirection::DESC))
+        };
+
+        // when
+        let team = runner.engine.query(search("miami")).unwrap();
+        let team_boosted = runner
+            .engine
+            .query(search("roger").with_boost(Boost::new("team", 3.0)))
+            .unwrap();
+        let name_boosted = runner
+            .engine
+            .query(search("roger").with_boost(Boost::new("name", 3.0)))
+            .unwrap();
+
+        // then
+        assert_eq!(team.items, vec![messi]);
+        assert_eq!(team_boosted.items, vec![jordan.clone(), ROGER.clone()]);
+        assert_eq!(name_boosted.items, vec![ROGER.clone(), jordan]);
+    }
+
+    #[test]
+    fn query_enum_eq_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+        ]);
+
+        let filter = CompositeFilter::eq("sport", FieldValue::str("Football"));
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone()]
+        );
+    }
+
+    #[test]
+    fn query_bool_eq_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+        ]);
+
+        let filter = CompositeFilter::eq("active", FieldValue::bool(false));
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(matches, vec![MICHAEL_JORDAN.clone()]);
+    }
+
+    #[test]
+    fn query_date_ge_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::ge("birth_date", FieldValue::str("1990-01-01"));
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(matches, vec![ROGER.clone()]);
+    }
+
+    #[test]
+    fn query_date_between_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::between(
+            "birth_date",
+            FieldValue::str("1970-01-01"),
+            FieldValue::str("1990-01-01"),
+        );
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone()]
+        );
+    }
+
+    #[test]
+    fn query_numeric_between_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::between("score", FieldValue::dec(6.0), FieldValue::dec(10.0));
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(matches, vec![MICHAEL_JORDAN.clone(), LIONEL_MESSI.clone()]);
+    }
+
+    #[test]
+    fn query_numeric_between_filter_from_text() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            ROGER.clone(),
+        ]);
+
+        let query = format!("FROM {} WHERE score BETWEEN 6 AND 9.5", runner.name);
+
+        // when
+        let matches = runner
+            .engine
+            .query(QueryExecution::parse_query(&query).unwrap())
+            .unwrap()
+            .items;
+
+        // then
+        assert_eq!(matches, vec![LIONEL_MESSI.clone()]);
+    }
+
+    #[test]
+    fn query_numeric_ge_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::ge("score", FieldValue::dec(6.0));
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(matches, vec![MICHAEL_JORDAN.clone(), LIONEL_MESSI.clone()]);
+    }
+
+    #[test]
+    fn query_numeric_le_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::le("score", FieldValue::dec(6.0));
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(matches, vec![ROGER.clone()]);
+    }
+
+    #[test]
+    fn query_and_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::and(vec![
+            CompositeFilter::ge("score", FieldValue::dec(2.0)),
+            CompositeFilter::eq("active", FieldValue::Bool(false)),
+        ]);
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(matches, vec![MICHAEL_JORDAN.clone(), ROGER.clone()]);
+    }
+
+    #[test]
+    fn query_or_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::or(vec![
+            CompositeFilter::ge("score", FieldValue::dec(9.0)),
+            CompositeFilter::le("birth_date", FieldValue::str("1990-01-01")),
+        ]);
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![
+                MICHAEL_JORDAN.clone(),
+                LIONEL_MESSI.clone(),
+                CRISTIANO_RONALDO.clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn query_not_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::negate(CompositeFilter::eq(
+            "sport",
+            FieldValue::String(Sport::Basketball.as_string()),
+        ));
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![
+                LIONEL_MESSI.clone(),
+                CRISTIANO_RONALDO.clone(),
+                ROGER.clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_not_group_filter_from_text() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let query = format!(
+            "FROM {} WHERE NOT (sport = \"Basketball\" OR active = false)",
+            runner.name
+        );
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(QueryExecution::parse_query(&query).unwrap())
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone()]
+        );
+    }
+
+    #[test]
+    fn query_enum_in_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let filter = CompositeFilter::in_values(
+            "sport",
+            vec![FieldValue::str("Football"), FieldValue::str("Basketball")],
+        );
+
+        // when
+        let mut matches = runner
+            .engine
             .query(
                 QueryExecution::new()
                     .for_entity(runner.name.clone())
                     .with_filter(filter),
             )
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![
+                MICHAEL_JORDAN.clone(),
+                LIONEL_MESSI.clone(),
+                CRISTIANO_RONALDO.clone(),
+                ROGER.clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn query_not_in_filter_from_text() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+            DAVID.clone(),
+        ]);
+
+        // David has no score, so that he matches neither `IN` nor `NOT IN`
+        let query = format!(
+            "FROM {} WHERE score NOT IN [5, 10] AND name IN [\"Lionel Messi\", \"Roger\", \"David\"]",
+            runner.name
+        );
+
+        // when
+        let mut matches = runner
+            .engine
+            .query(QueryExecution::parse_query(&query).unwrap())
+            .unwrap()
+            .items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(matches, vec![LIONEL_MESSI.clone()]);
+    }
+
+    #[test]
+    fn query_numeric_delta() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+        ]);
+
+        let delta_scope =
+            DeltaScope::date(Date::from_calendar_date(2023, Month::January, 1).unwrap());
+        let deltas = vec![
+            DecreaseScoreDelta::create(MICHAEL_JORDAN.id, 10.0),
+            DecreaseScoreDelta::create(LIONEL_MESSI.id, 9.0),
+        ];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        // when
+        let execution = QueryExecution::new()
+            .for_entity(runner.name.clone())
+            .with_filter(CompositeFilter::eq("sport", FieldValue::str("Football")))
+            .with_scope(DeltaScope::date(
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            ));
+
+        let mut matches = runner.engine.query(execution).unwrap().items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![
+                Player {
+                    id: LIONEL_MESSI.id,
+                    name: "Lionel Messi".to_string(),
+                    score: Some(8.0),
+                    sport: Sport::Football,
+                    birth_date: "1987-06-24".to_string(),
+                    active: true,
+                }
+                .as_item(),
+                CRISTIANO_RONALDO.clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_enum_delta() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+        ]);
+
+        let delta_scope =
+            DeltaScope::date(Date::from_calendar_date(2023, Month::January, 1).unwrap());
+        let deltas = vec![SwitchSportsDelta::create(
+            MICHAEL_JORDAN.id,
+            Sport::Football,
+        )];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        // when
+        let execution = QueryExecution::new()
+            .for_entity(runner.name.clone())
+            .with_filter(CompositeFilter::eq("sport", FieldValue::str("Football")))
+            .with_scope(DeltaScope::date(
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            ));
+
+        let mut matches = runner.engine.query(execution).unwrap().items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![
+                Player {
+                    id: 0,
+                    name: "Michael Jordan".to_string(),
+                    score: Some(10.0),
+                    sport: Sport::Football,
+                    birth_date: "1963-02-17".to_string(),
+                    active: false,
+                }
+                .as_item(),
+                LIONEL_MESSI.clone(),
+                CRISTIANO_RONALDO.clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_term_delta() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+        ]);
+
+        let delta_scope =
+            DeltaScope::date(Date::from_calendar_date(2023, Month::January, 1).unwrap());
+        let deltas = vec![DeltaChange::new(
+            LIONEL_MESSI.id,
+            "name".to_string(),
+            FieldValue::String("Lionel Roccuzzo".to_string()),
+        )];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        // when
+        let execution = QueryExecution::new()
+            .for_entity(runner.name.clone())
+            .with_filter(CompositeFilter::contains(
+                "name",
+                FieldValue::str("Roccuzzo"),
+            ))
+            .with_scope(DeltaScope::date(
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            ));
+
+        let mut matches = runner.engine.query(execution).unwrap().items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![
+                Player::new(1, "Lionel Roccuzzo", Sport::Football, "1987-06-24", true)
+                    .with_score(9.0)
+                    .as_item()
+            ]
+        );
+
+        // when
+        let execution = QueryExecution::new()
+            .for_entity(runner.name.clone())
+            .with_filter(CompositeFilter::matches(
+                "name",
+                FieldValue::str("Lionel Roccuzzo"),
+            ))
+            .with_scope(DeltaScope::date(
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            ));
+
+        let mut matches = runner.engine.query(execution).unwrap().items;
+
+        // then
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches,
+            vec![
+                Player::new(1, "Lionel Roccuzzo", Sport::Football, "1987-06-24", true)
+                    .with_score(9.0)
+                    .as_item()
+            ]
+        );
+    }
+
+    #[test]
+    fn query_with_delta_branch() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+        ]);
+
+        let branch = 0;
+        let delta_scope = DeltaScope::branch(
+            branch,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+
+        let deltas = vec![SwitchSportsDelta::create(
+            MICHAEL_JORDAN.id,
+            Sport::Football,
+        )];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
             .unwrap();
 
+        // when
+        let filter = CompositeFilter::eq("sport", FieldValue::str("Football"));
+
+        let execution_without_branch = QueryExecution::new()
+            .for_entity(runner.name.clone())
+            .with_filter(filter.clone())
+            .with_scope(DeltaScope::date(
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            ));
+
+        let mut matches_without_branch =
+            runner.engine.query(execution_without_branch).unwrap().items;
+
+        // then
+        matches_without_branch.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            matches_without_branch,
+            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone(),]
+        );
+
+        // when
+        let execution_with_branch = QueryExecution::new()
+            .for_entity(runner.name.clone())
+            .with_filter(filter.clone())
+            .with_scope(DeltaScope::branch(
+                branch,
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            ));
+
+        let mut matches_with_branch = runner.engine.query(execution_with_branch).unwrap().items;
+
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
+        matches_with_branch.sort_by(|a, b| a.id.cmp(&b.id));
 
         assert_eq!(
-            matches,
+            matches_with_branch,
             vec![
-                MICHAEL_JORDAN.clone(),
+                Player {
+                    id: 0,
+                    name: "Michael Jordan".to_string(),
+                    score: Some(10.0),
+                    sport: Sport::Football,
+                    birth_date: "1963-02-17".to_string(),
+                    active: false,
+                }
+                .as_item(),
                 LIONEL_MESSI.clone(),
-                CRISTIANO_RONALDO.clone()
+                CRISTIANO_RONALDO.clone(),
             ]
         );
     }
 
     #[test]
-    fn query_not_filter() {
+    fn query_aggregation_group_by() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
             ROGER.clone(),
+            DAVID.clone(),
         ]);
 
-        let filter = CompositeFilter::negate(CompositeFilter::eq(
-            "sport",
-            FieldValue::String(Sport::Basketball.as_string()),
-        ));
+        let query = format!(
+            "FROM {} GROUP BY active AGG avg(score), max(birth_date), min(name), count(*)",
+            runner.name
+        );
 
         // when
-        let mut matches = runner
+        let groups = runner
             .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_filter(filter),
-            )
-            .unwrap();
+            .query(QueryExecution::parse_query(&query).unwrap())
+            .unwrap()
+            .groups;
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
-
         assert_eq!(
-            matches,
+            groups,
             vec![
-                LIONEL_MESSI.clone(),
-                CRISTIANO_RONALDO.clone(),
-                ROGER.clone(),
+                AggregationGroup {
+                    key: Some(FieldValue::bool(false)),
+                    count: 3,
+                    values: vec![
+                        AggregationValue {
+                            name: "avg(score)".to_string(),
+                            value: Some(FieldValue::dec(7.5)),
+                        },
+                        AggregationValue {
+                            name: "max(birth_date)".to_string(),
+                            value: Some(FieldValue::str("1996-05-01")),
+                        },
+                        AggregationValue {
+                            name: "min(name)".to_string(),
+                            value: Some(FieldValue::str("David")),
+                        },
+                        AggregationValue {
+                            name: "count(*)".to_string(),
+                            value: Some(FieldValue::int(3)),
+                        },
+                    ],
+                },
+                AggregationGroup {
+                    key: Some(FieldValue::bool(true)),
+                    count: 2,
+                    values: vec![
+                        AggregationValue {
+                            name: "avg(score)".to_string(),
+                            value: Some(FieldValue::dec(9.0)),
+                        },
+                        AggregationValue {
+                            name: "max(birth_date)".to_string(),
+                            value: Some(FieldValue::str("1987-06-24")),
+                        },
+                        AggregationValue {
+                            name: "min(name)".to_string(),
+                            value: Some(FieldValue::str("Cristiano Ronaldo")),
+                        },
+                        AggregationValue {
+                            name: "count(*)".to_string(),
+                            value: Some(FieldValue::int(2)),
+                        },
+                    ],
+                },
             ]
         );
     }
 
     #[test]
-    fn query_numeric_delta() {
+    fn query_aggregation_delta() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
         ]);
 
-        let delta_scope =
-            DeltaScope::date(Date::from_calendar_date(2023, Month::January, 1).unwrap());
-        let deltas = vec![
-            DecreaseScoreDelta::create(MICHAEL_JORDAN.id, 10.0),
-            DecreaseScoreDelta::create(LIONEL_MESSI.id, 9.0),
-        ];
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DecreaseScoreDelta::create(LIONEL_MESSI.id, 9.0)];
 
         runner
             .engine
             .store_deltas(&runner.name, &delta_scope, deltas)
             .unwrap();
 
+        let aggregate = || {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::eq("active", FieldValue::bool(true)))
+                .with_group_by("sport")
+                .with_aggregation(Aggregation::new(AggregateFunction::Avg, "score"))
+                .with_aggregation(Aggregation::new(AggregateFunction::Sum, "score"))
+        };
+
         // when
-        let execution = QueryExecution::new()
-            .for_entity(runner.name.clone())
-            .with_filter(CompositeFilter::eq("sport", FieldValue::str("Football")))
-            .with_scope(DeltaScope::date(
+        let current = runner.engine.query(aggregate()).unwrap().groups;
+        let branch = runner
+            .engine
+            .query(aggregate().with_scope(DeltaScope::branch(
+                0,
                 Date::from_calendar_date(2024, Month::January, 1).unwrap(),
-            ));
-
-        let mut matches = runner.engine.query(execution).unwrap();
+            )))
+            .unwrap()
+            .groups;
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
-
         assert_eq!(
-            matches,
-            vec![
-                Player {
-                    id: LIONEL_MESSI.id,
-                    name: "Lionel Messi".to_string(),
-                    score: Some(8.0),
-                    sport: Sport::Football,
-                    birth_date: "1987-06-24".to_string(),
-                    active: true,
-                }
-                .as_item(),
-                CRISTIANO_RONALDO.clone(),
-            ]
+            current,
+            vec![AggregationGroup {
+                key: Some(FieldValue::str("Football")),
+                count: 2,
+                values: vec![
+                    AggregationValue {
+                        name: "avg(score)".to_string(),
+                        value: Some(FieldValue::dec(9.0)),
+                    },
+                    AggregationValue {
+                        name: "sum(score)".to_string(),
+                        value: Some(FieldValue::dec(18.0)),
+                    },
+                ],
+            }]
+        );
+        assert_eq!(
+            branch,
+            vec![AggregationGroup {
+                key: Some(FieldValue::str("Football")),
+                count: 2,
+                values: vec![
+                    AggregationValue {
+                        name: "avg(score)".to_string(),
+                        value: Some(FieldValue::dec(8.5)),
+                    },
+                    AggregationValue {
+                        name: "sum(score)".to_string(),
+                        value: Some(FieldValue::dec(17.0)),
+                    },
+                ],
+            }]
         );
     }
 
     #[test]
-    fn query_enum_delta() {
+    fn query_null_filters() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
-            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+            DAVID.clone(),
         ]);
 
-        let delta_scope =
-            DeltaScope::date(Date::from_calendar_date(2023, Month::January, 1).unwrap());
-        let deltas = vec![SwitchSportsDelta::create(
-            MICHAEL_JORDAN.id,
-            Sport::Football,
+        let query = |filter: &str| {
+            let execution =
+                QueryExecution::parse_query(&format!("FROM {} WHERE {}", runner.name, filter))
+                    .unwrap();
+
+            let mut ids: Vec<DataItemId> = runner
+                .engine
+                .query(execution)
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| item.id)
+                .collect();
+
+            ids.sort();
+            ids
+        };
+
+        // when
+        let null = query("score IS NULL");
+        let not_null = query("score IS NOT NULL");
+        let exists = query("score EXISTS AND sport = \"Football\"");
+
+        // then
+        assert_eq!(null, vec![DAVID.id]);
+        assert_eq!(not_null, vec![MICHAEL_JORDAN.id, LIONEL_MESSI.id, ROGER.id]);
+        assert_eq!(exists, vec![LIONEL_MESSI.id, ROGER.id]);
+    }
+
+    #[test]
+    fn query_null_filter_delta() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            DAVID.clone(),
+        ]);
+
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DeltaChange::new(
+            DAVID.id,
+            "score".to_string(),
+            FieldValue::dec(7.0),
         )];
 
         runner
@@ -597,195 +1909,366 @@ mod tests {
             .store_deltas(&runner.name, &delta_scope, deltas)
             .unwrap();
 
+        let null_scores = || {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_filter(CompositeFilter::is_null("score"))
+        };
+
         // when
-        let execution = QueryExecution::new()
-            .for_entity(runner.name.clone())
-            .with_filter(CompositeFilter::eq("sport", FieldValue::str("Football")))
-            .with_scope(DeltaScope::date(
+        let current = runner.engine.query(null_scores()).unwrap().items;
+        let branch = runner
+            .engine
+            .query(null_scores().with_scope(DeltaScope::branch(
+                0,
                 Date::from_calendar_date(2024, Month::January, 1).unwrap(),
-            ));
+            )))
+            .unwrap()
+            .items;
+
+        // then
+        assert_eq!(current, vec![DAVID.clone()]);
+        assert!(branch.is_empty());
+    }
+
+    #[test]
+    fn query_explain() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+        ]);
+
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DecreaseScoreDelta::create(LIONEL_MESSI.id, 9.0)];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        let execution = QueryExecution::parse_query(&format!(
+            "EXPLAIN FROM {} WHERE active = true AND (name CONTAINS \"Cristiano\" OR score < 9) BRANCH 0 AS OF \"2024-01-01\" ORDER BY score DESC",
+            runner.name
+        ))
+        .unwrap();
+
+        // when
+        let result = runner.engine.query(execution).unwrap();
+
+        // then
+        let mut ids: Vec<DataItemId> = result.items.iter().map(|item| item.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![LIONEL_MESSI.id, CRISTIANO_RONALDO.id]);
+
+        let plan = result.plan.unwrap();
+        assert_eq!(plan.total, 2);
+        assert_eq!(
+            plan.deltas,
+            Some(DeltaPlan {
+                branch: Some(0),
+                date: "2024-01-01".to_string(),
+                fields: vec!["score".to_string()],
+                affected_items: 1,
+            })
+        );
+        assert_eq!(plan.sort.strategy, SortStrategy::Indices);
+        assert_eq!(plan.sort.keys, vec!["score DESC".to_string()]);
+
+        let filter = plan.filter.unwrap();
+        assert_eq!(filter.node, FilterNode::And);
+        assert_eq!(filter.cardinality, 2);
+
+        let [active, or] = filter.children.as_slice() else {
+            panic!("expected two children in AND node");
+        };
+        assert_eq!(active.filter.as_deref(), Some("active = true"));
+        assert_eq!(active.index.as_deref(), Some("bool"));
+        assert_eq!(active.cardinality, 2);
+
+        assert_eq!(or.node, FilterNode::Or);
+        assert_eq!(or.cardinality, 3);
+
+        let [name, score] = or.children.as_slice() else {
+            panic!("expected two children in OR node");
+        };
+        assert_eq!(name.index.as_deref(), Some("term"));
+        assert_eq!(name.cardinality, 1);
+        assert_eq!(score.index.as_deref(), Some("numeric"));
+        assert_eq!(score.cardinality, 2);
+    }
+
+    #[test]
+    fn explain_without_filter() {
+        // given
+        let runner = STORAGES.start_runner(vec![MICHAEL_JORDAN.clone(), LIONEL_MESSI.clone()]);
+
+        // when
+        let plan = runner
+            .engine
+            .explain(QueryExecution::new().for_entity(runner.name.clone()))
+            .unwrap();
+
+        // then
+        assert_eq!(plan.entity, runner.name);
+        assert_eq!(plan.total, 2);
+        assert_eq!(plan.deltas, None);
+        assert_eq!(plan.filter, None);
+        assert_eq!(plan.sort.strategy, SortStrategy::Id);
+        assert!(plan.sort.keys.is_empty());
+    }
+
+    #[test]
+    fn explain_unknown_entity() {
+        // given
+        let runner = STORAGES.start_runner(vec![MICHAEL_JORDAN.clone()]);
+
+        // when
+        let plan = runner
+            .engine
+            .explain(QueryExecution::new().for_entity("unknown".to_string()))
+            .unwrap();
+
+        // then
+        assert_eq!(plan.entity, "unknown");
+        assert_eq!(plan.total, 0);
+        assert_eq!(plan.filter, None);
+    }
+
+    #[test]
+    fn query_pagination() {
+        // given
+        let runner = STORAGES.start_runner(create_random_players(20));
+
+        let filter = CompositeFilter::eq("sport", FieldValue::str("Football"));
+        let sort = Sort::new("score");
+        let pagination = Pagination::new(2, 5);
 
-        let mut matches = runner.engine.query(execution).unwrap();
+        // when
+        let matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter)
+                    .with_sort(sort)
+                    .with_pagination(pagination),
+            )
+            .unwrap()
+            .items;
+
+        // then
+        assert_eq!(
+            matches,
+            vec![
+                create_player_from_index(5),
+                create_player_from_index(7),
+                create_player_from_index(9),
+                create_player_from_index(11),
+                create_player_from_index(13),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_pagination_metadata() {
+        // given
+        let runner = STORAGES.start_runner(create_random_players(20));
+
+        let filter = CompositeFilter::eq("sport", FieldValue::str("Football"));
+
+        // when
+        let first_page = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter.clone())
+                    .with_pagination(Pagination::new(0, 8)),
+            )
+            .unwrap();
+
+        let last_page = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_filter(filter)
+                    .with_pagination(Pagination::new(8, 8)),
+            )
+            .unwrap();
+
+        // then
+        assert_eq!(first_page.total, 10);
+        assert_eq!(first_page.start, 0);
+        assert_eq!(first_page.size, 8);
+        assert!(first_page.has_more);
+
+        assert_eq!(last_page.total, 10);
+        assert_eq!(last_page.start, 8);
+        assert_eq!(last_page.size, 2);
+        assert!(!last_page.has_more);
+    }
+
+    #[test]
+    fn query_sort_numeric_asc() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+            DAVID.clone(),
+        ]);
+
+        let sort = Sort::new("score").with_direction(SortDirection::ASC);
+
+        // when
+        let matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_sort(sort),
+            )
+            .unwrap()
+            .items;
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
-
         assert_eq!(
             matches,
             vec![
-                Player {
-                    id: 0,
-                    name: "Michael Jordan".to_string(),
-                    score: Some(10.0),
-                    sport: Sport::Football,
-                    birth_date: "1963-02-17".to_string(),
-                    active: false,
-                }
-                .as_item(),
-                LIONEL_MESSI.clone(),
+                ROGER.clone(),
                 CRISTIANO_RONALDO.clone(),
+                MICHAEL_JORDAN.clone(),
+                DAVID.clone(),
             ]
         );
     }
 
     #[test]
-    fn query_term_delta() {
+    fn query_sort_numeric_desc() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
-            LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+            DAVID.clone(),
         ]);
 
-        let delta_scope =
-            DeltaScope::date(Date::from_calendar_date(2023, Month::January, 1).unwrap());
-        let deltas = vec![DeltaChange::new(
-            LIONEL_MESSI.id,
-            "name".to_string(),
-            FieldValue::String("Lionel Roccuzzo".to_string()),
-        )];
-
-        runner
-            .engine
-            .store_deltas(&runner.name, &delta_scope, deltas)
-            .unwrap();
+        let sort = Sort::new("score").with_direction(SortDirection::DESC);
 
         // when
-        let execution = QueryExecution::new()
-            .for_entity(runner.name.clone())
-            .with_filter(CompositeFilter::contains(
-                "name",
-                FieldValue::str("Roccuzzo"),
-            ))
-            .with_scope(DeltaScope::date(
-                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
-            ));
-
-        let mut matches = runner.engine.query(execution).unwrap();
+        let matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_sort(sort),
+            )
+            .unwrap()
+            .items;
 
         // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
-
         assert_eq!(
             matches,
             vec![
-                Player::new(1, "Lionel Roccuzzo", Sport::Football, "1987-06-24", true)
-                    .with_score(9.0)
-                    .as_item()
+                MICHAEL_JORDAN.clone(),
+                CRISTIANO_RONALDO.clone(),
+                ROGER.clone(),
+                DAVID.clone(),
             ]
         );
+    }
 
-        // when
-        let execution = QueryExecution::new()
-            .for_entity(runner.name.clone())
-            .with_filter(CompositeFilter::matches(
-                "name",
-                FieldValue::str("Lionel Roccuzzo"),
-            ))
-            .with_scope(DeltaScope::date(
-                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
-            ));
+    #[test]
+    fn query_sort_numeric_desc_nulls_first() {
+        // given
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+            DAVID.clone(),
+        ]);
 
-        let mut matches = runner.engine.query(execution).unwrap();
+        let sort = Sort::new("score")
+            .with_direction(SortDirection::DESC)
+            .with_nulls(NullsPlacement::First);
 
-        // then
-        matches.sort_by(|a, b| a.id.cmp(&b.id));
+        // when
+        let matches = runner
+            .engine
+            .query(
+                QueryExecution::new()
+                    .for_entity(runner.name.clone())
+                    .with_sort(sort),
+            )
+            .unwrap()
+            .items;
 
+        // then
         assert_eq!(
             matches,
             vec![
-                Player::new(1, "Lionel Roccuzzo", Sport::Football, "1987-06-24", true)
-                    .with_score(9.0)
-                    .as_item()
+                DAVID.clone(),
+                MICHAEL_JORDAN.clone(),
+                CRISTIANO_RONALDO.clone(),
+                ROGER.clone(),
             ]
         );
     }
 
     #[test]
-    fn query_with_delta_branch() {
+    fn query_sort_multiple_keys() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
             LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
+            ROGER.clone(),
+            DAVID.clone(),
         ]);
 
-        let branch = 0;
-        let delta_scope = DeltaScope::branch(
-            branch,
-            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
-        );
-
-        let deltas = vec![SwitchSportsDelta::create(
-            MICHAEL_JORDAN.id,
-            Sport::Football,
-        )];
-
-        runner
-            .engine
-            .store_deltas(&runner.name, &delta_scope, deltas)
-            .unwrap();
-
-        // when
-        let filter = CompositeFilter::eq("sport", FieldValue::str("Football"));
-
-        let execution_without_branch = QueryExecution::new()
-            .for_entity(runner.name.clone())
-            .with_filter(filter.clone())
-            .with_scope(DeltaScope::date(
-                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
-            ));
-
-        let mut matches_without_branch = runner.engine.query(execution_without_branch).unwrap();
-
-        // then
-        matches_without_branch.sort_by(|a, b| a.id.cmp(&b.id));
-
-        assert_eq!(
-            matches_without_branch,
-            vec![LIONEL_MESSI.clone(), CRISTIANO_RONALDO.clone(),]
+        let query = format!(
+            "FROM {} ORDER BY active DESC, score DESC, name ASC",
+            runner.name
         );
 
         // when
-        let execution_with_branch = QueryExecution::new()
-            .for_entity(runner.name.clone())
-            .with_filter(filter.clone())
-            .with_scope(DeltaScope::branch(
-                branch,
-                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
-            ));
-
-        let mut matches_with_branch = runner.engine.query(execution_with_branch).unwrap();
+        let matches = runner
+            .engine
+            .query(QueryExecution::parse_query(&query).unwrap())
+            .unwrap()
+            .items;
 
         // then
-        matches_with_branch.sort_by(|a, b| a.id.cmp(&b.id));
-
         assert_eq!(
-            matches_with_branch,
+            matches,
             vec![
-                Player {
-                    id: 0,
-                    name: "Michael Jordan".to_string(),
-                    score: Some(10.0),
-                    sport: Sport::Football,
-                    birth_date: "1963-02-17".to_string(),
-                    active: false,
-                }
-                .as_item(),
-                LIONEL_MESSI.clone(),
                 CRISTIANO_RONALDO.clone(),
+                LIONEL_MESSI.clone(),
+                MICHAEL_JORDAN.clone(),
+                ROGER.clone(),
+                DAVID.clone(),
             ]
         );
     }
 
     #[test]
-    fn query_pagination() {
+    fn query_sort_ties_by_id() {
         // given
-        let runner = STORAGES.start_runner(create_random_players(20));
+        let runner = STORAGES.start_runner(vec![
+            MICHAEL_JORDAN.clone(),
+            CRISTIANO_RONALDO.clone(),
+            LIONEL_MESSI.clone(),
+            ROGER.clone(),
+        ]);
 
-        let filter = CompositeFilter::eq("sport", FieldValue::str("Football"));
-        let sort = Sort::new("score");
-        let pagination = Pagination::new(2, 5);
+        let sort = Sort::new("score").with_direction(SortDirection::DESC);
 
         // when
         let matches = runner
@@ -793,70 +2276,142 @@ mod tests {
             .query(
                 QueryExecution::new()
                     .for_entity(runner.name.clone())
-                    .with_filter(filter)
-                    .with_sort(sort)
-                    .with_pagination(pagination),
+                    .with_sort(sort),
             )
-            .unwrap();
+            .unwrap()
+            .items;
 
         // then
         assert_eq!(
             matches,
             vec![
-                create_player_from_index(5),
-                create_player_from_index(7),
-                create_player_from_index(9),
-                create_player_from_index(11),
-                create_player_from_index(13),
+                MICHAEL_JORDAN.clone(),
+                LIONEL_MESSI.clone(),
+                CRISTIANO_RONALDO.clone(),
+                ROGER.clone(),
             ]
         );
     }
 
     #[test]
-    fn query_sort_numeric_asc() {
+    fn query_projection_from_indices() {
         // given
         let runner = STORAGES.start_runner(vec![
             MICHAEL_JORDAN.clone(),
+            LIONEL_MESSI.clone(),
             CRISTIANO_RONALDO.clone(),
-            ROGER.clone(),
-            DAVID.clone(),
         ]);
 
-        let sort = Sort::new("score").with_direction(SortDirection::ASC);
+        let query = format!(
+            "SELECT name, birth_date FROM {} WHERE sport = \"Football\"",
+            runner.name
+        );
 
         // when
         let matches = runner
             .engine
-            .query(
-                QueryExecution::new()
-                    .for_entity(runner.name.clone())
-                    .with_sort(sort),
-            )
-            .unwrap();
+            .query(QueryExecution::parse_query(&query).unwrap())
+            .unwrap()
+            .items;
 
         // then
         assert_eq!(
             matches,
             vec![
-                ROGER.clone(),
-                CRISTIANO_RONALDO.clone(),
-                MICHAEL_JORDAN.clone(),
-                DAVID.clone(),
+                DataItem::new(
+                    LIONEL_MESSI.id,
+                    BTreeMap::from([
+                        ("name".to_string(), FieldValue::str("Lionel Messi")),
+                        ("birth_date".to_string(), FieldValue::str("1987-06-24")),
+                    ])
+                ),
+                DataItem::new(
+                    CRISTIANO_RONALDO.id,
+                    BTreeMap::from([
+                        ("name".to_string(), FieldValue::str("Cristiano Ronaldo")),
+                        ("birth_date".to_string(), FieldValue::str("1985-02-05")),
+                    ])
+                ),
             ]
         );
     }
 
     #[test]
-    fn query_sort_numeric_desc() {
+    fn query_projection_of_integer_field() {
         // given
+        let with_titles = |item: &DataItem, titles: u64| {
+            let mut item = item.clone();
+            item.fields
+                .insert("titles".to_string(), FieldValue::int(titles));
+            item
+        };
+
         let runner = STORAGES.start_runner(vec![
-            MICHAEL_JORDAN.clone(),
-            CRISTIANO_RONALDO.clone(),
-            ROGER.clone(),
-            DAVID.clone(),
+            with_titles(&MICHAEL_JORDAN, 6),
+            with_titles(&LIONEL_MESSI, 1),
         ]);
 
-        let sort = Sort::new("score").with_direction(SortDirection::DESC);
+        runner
+            .engine
+            .create_index(
+                &runner.name,
+                CreateFieldIndex {
+                    name: "titles".to_string(),
+                    descriptor: TypeDescriptor::Numeric,
+                },
+            )
+            .unwrap();
+
+        let delta_scope = DeltaScope::branch(
+            0,
+            Date::from_calendar_date(2023, Month::January, 1).unwrap(),
+        );
+        let deltas = vec![DeltaChange::new(
+            LIONEL_MESSI.id,
+            "titles".to_string(),
+            FieldValue::int(2),
+        )];
+
+        runner
+            .engine
+            .store_deltas(&runner.name, &delta_scope, deltas)
+            .unwrap();
+
+        let project = || {
+            QueryExecution::new()
+                .for_entity(runner.name.clone())
+                .with_projection(vec!["titles".to_string()])
+        };
+        let titles = |items: Vec<DataItem>| {
+            items
+                .into_iter()
+                .map(|item| item.fields["titles"].clone())
+                .collect::<Vec<_>>()
+        };
+
+        // when
+        let current = runner.engine.query(project()).unwrap().items;
+        let branch = runner
+            .engine
+            .query(project().with_scope(DeltaScope::branch(
+                0,
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            )))
+            .unwrap()
+            .items;
+
+        // then
+        assert_eq!(
+            titles(current),
+            vec![FieldValue::int(6), FieldValue::int(1)]
+        );
+        assert_eq!(titles(branch), vec![FieldValue::int(6), FieldValue::int(2)]);
+    }
+
+    #[test]
+    fn query_projection_with_non_indexed_field() {
+        // given
+        let runner = STORAGES.start_runner(vec![MICHAEL_JORDAN.clone(), ROGER.clone()]);
 
         // when
         let matches = runner
@@ -864,18 +2419,24 @@ mod tests {
             .query(
                 QueryExecution::new()
                     .for_entity(runner.name.clone())
-                    .with_sort(sort),
+                    .with_filter(CompositeFilter::eq("active", FieldValue::bool(false)))
+                    .with_projection(vec!["score".to_string(), "nickname".to_string()]),
             )
-            .unwrap();
+            .unwrap()
+            .items;
 
         // then
         assert_eq!(
             matches,
             vec![
-                MICHAEL_JORDAN.clone(),
-                CRISTIANO_RONALDO.clone(),
-                ROGER.clone(),
-                DAVID.clone(),
+                DataItem::new(
+                    MICHAEL_JORDAN.id,
+                    BTreeMap::from([("score".to_string(), FieldValue::dec(10.0))])
+                ),
+                DataItem::new(
+                    ROGER.id,
+                    BTreeMap::from([("score".to_string(), FieldValue::dec(5.0))])
+                ),
             ]
         );
     }
@@ -905,9 +2466,10 @@ mod tests {
             vec![
                 FilterOption::new(
                     "active".to_string(),
-                    BTreeMap::from_iter([("true".to_string(), 2), ("false".to_string(), 3)])
+                    BTreeMap::from_iter([("true".to_string(), 2), ("false".to_string(), 3)]),
+                    0
                 ),
-                FilterOption::new("birth_date".to_string(), BTreeMap::from_iter([])),
+                FilterOption::new("birth_date".to_string(), BTreeMap::from_iter([]), 0),
                 FilterOption::new(
                     "name".to_string(),
                     BTreeMap::from_iter([
@@ -917,6 +2479,7 @@ mod tests {
                         ("Roger".to_string(), 1),
                         ("David".to_string(), 1)
                     ]),
+                    0
                 ),
                 FilterOption::new(
                     "score".to_string(),
@@ -925,6 +2488,7 @@ mod tests {
                         ("9".to_string(), 2),
                         ("10".to_string(), 1)
                     ]),
+                    1
                 ),
                 FilterOption::new(
                     "sport".to_string(),
@@ -932,6 +2496,7 @@ mod tests {
                         ("Basketball".to_string(), 2),
                         ("Football".to_string(), 3)
                     ]),
+                    0
                 )
             ]
         );
@@ -967,9 +2532,10 @@ mod tests {
             vec![
                 FilterOption::new(
                     "active".to_string(),
-                    BTreeMap::from_iter([("true".to_string(), 2), ("false".to_string(), 1)])
+                    BTreeMap::from_iter([("true".to_string(), 2), ("false".to_string(), 1)]),
+                    0
                 ),
-                FilterOption::new("birth_date".to_string(), BTreeMap::from_iter([])),
+                FilterOption::new("birth_date".to_string(), BTreeMap::from_iter([]), 0),
                 FilterOption::new(
                     "name".to_string(),
                     BTreeMap::from_iter([
@@ -979,6 +2545,7 @@ mod tests {
                         ("Michael Jordan".to_string(), 1),
                         ("Roger".to_string(), 0),
                     ]),
+                    0
                 ),
                 FilterOption::new(
                     "score".to_string(),
@@ -987,6 +2554,7 @@ mod tests {
                         ("9".to_string(), 2),
                         ("10".to_string(), 1)
                     ]),
+                    0
                 ),
                 FilterOption::new(
                     "sport".to_string(),
@@ -994,6 +2562,7 @@ mod tests {
                         ("Basketball".to_string(), 1),
                         ("Football".to_string(), 2)
                     ]),
+                    0
                 )
             ]
         );
@@ -1018,7 +2587,7 @@ mod tests {
                 "name",
                 FieldValue::String("Roger".to_string()),
             ));
-        let matches = runner.engine.query(query).unwrap();
+        let matches = runner.engine.query(query).unwrap().items;
 
         assert_eq!(matches, vec![ROGER.clone()]);
     }
@@ -1045,7 +2614,7 @@ mod tests {
                 "name",
                 FieldValue::String("Cristiano Ronaldo".to_string()),
             ));
-        let matches = runner.engine.query(query).unwrap();
+        let matches = runner.engine.query(query).unwrap().items;
 
         assert!(matches.is_empty());
     }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use anyhow::anyhow;
@@ -5,21 +6,25 @@ use axum::body::Body;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::routing::{post, put};
+use axum::routing::{get, post, put};
 use axum::{response::Json, Router};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::format_description::well_known::Iso8601;
 use time::Date;
 
+use delta_search::analysis::{Analyzer, SynonymSet};
 use delta_search::data::{
     DataItem, DataItemFieldsExternal, DataItemId, FieldValue, FieldValueExternal,
 };
 use delta_search::index::{StringTypeDescriptor, TypeDescriptor};
 use delta_search::query::{
-    DeltaChange, DeltaScope, FilterOption, OptionsQueryExecution, QueryExecution,
+    AggregationGroup, DeltaChange, DeltaScope, ErrorSpan, FilterOption, Highlight,
+    OptionsQueryExecution, ParseError, Query, QueryExecution, QueryPlan, QueryResult,
+    SimilarExecution, SpellingSuggestion, SuggestExecution, Suggestion,
 };
-use delta_search::storage::CreateFieldIndex;
+use delta_search::storage::{CreateFieldIndex, StorageError};
 use delta_search::{Engine, EngineError};
 use tracing::{error, info};
 
@@ -88,23 +93,38 @@ impl App {
         Ok(())
     }
 
-    fn query(&self, input: QueryInput) -> Result<Vec<DataItemExternal>, AppError> {
+    fn query(&self, input: QueryInput) -> Result<QueryResponse, AppError> {
         let execution = Self::build_query_execution(input)?;
+        self.run_query(execution)
+    }
+
+    fn query_json(&self, query: Query) -> Result<QueryResponse, AppError> {
+        self.run_query(QueryExecution::from_query(query))
+    }
 
+    fn run_query(&self, execution: QueryExecution) -> Result<QueryResponse, AppError> {
         self.inner
             .query(execution)
-            .map(|items| items.into_iter().map(DataItemExternal::from_item).collect())
+            .map(QueryResponse::from_result)
             .inspect_err(|err| error!("Query could not be executed: {}", err))
             .map_err(|_| anyhow!("Query could not be executed").into())
     }
 
     fn build_query_execution(input: QueryInput) -> Result<QueryExecution, AppError> {
-        QueryExecution::parse_query(&input.query).map_err(|_| AppError::InvalidFilterQuery)
+        QueryExecution::parse_query(&input.query)
+            .map_err(|err| AppError::invalid_filter_query(err, &input.query))
     }
 
     fn options(&self, input: QueryOptionsInput) -> Result<Vec<FilterOption>, AppError> {
         let execution = Self::build_options_execution(input)?;
+        self.run_options(execution)
+    }
+
+    fn options_json(&self, query: Query) -> Result<Vec<FilterOption>, AppError> {
+        self.run_options(OptionsQueryExecution::from_query(query))
+    }
 
+    fn run_options(&self, execution: OptionsQueryExecution) -> Result<Vec<FilterOption>, AppError> {
         self.inner
             .options(execution)
             .inspect_err(|err| error!("Could not create options: {}", err))
@@ -114,7 +134,66 @@ impl App {
     fn build_options_execution(
         input: QueryOptionsInput,
     ) -> Result<OptionsQueryExecution, AppError> {
-        OptionsQueryExecution::parse_query(&input.query).map_err(|_| AppError::InvalidFilterQuery)
+        OptionsQueryExecution::parse_query(&input.query)
+            .map_err(|err| AppError::invalid_filter_query(err, &input.query))
+    }
+
+    fn suggest(&self, input: SuggestInput) -> Result<Vec<Suggestion>, AppError> {
+        let mut execution =
+            SuggestExecution::parse_query(&input.query, &input.field, &input.prefix)
+                .map_err(|err| AppError::invalid_filter_query(err, &input.query))?;
+        if let Some(limit) = input.limit {
+            execution = execution.with_limit(limit);
+        }
+
+        self.inner
+            .suggest(execution)
+            .inspect_err(|err| error!("Could not create suggestions: {}", err))
+            .map_err(|_| anyhow!("Could not create suggestions").into())
+    }
+
+    fn similar(&self, input: SimilarInput) -> Result<Vec<DataItemExternal>, AppError> {
+        let mut execution = SimilarExecution::parse_query(&input.query, input.id, &input.fields)
+            .map_err(|err| AppError::invalid_filter_query(err, &input.query))?;
+        if let Some(limit) = input.limit {
+            execution = execution.with_limit(limit);
+        }
+
+        let items = self
+            .inner
+            .similar(execution)
+            .inspect_err(|err| error!("Could not find similar items: {}", err))
+            .map_err(|_| anyhow!("Could not find similar items"))?;
+
+        Ok(items
+            .into_iter()
+            .map(|similar| {
+                DataItemExternal::from_item(similar.item).with_score(Some(similar.score))
+            })
+            .collect())
+    }
+
+    fn store_synonyms(&self, name: &str, input: StoreSynonymsInput) -> Result<(), AppError> {
+        self.inner
+            .store_synonyms(name, &input.field, input.synonyms)
+            .inspect_err(|err| error!("Could not store synonyms: {}", err))
+            .map_err(|err| match err {
+                EngineError::Storage(
+                    err @ (StorageError::MissingIndex(_) | StorageError::Index(_)),
+                ) => AppError::InvalidRequest {
+                    message: err.to_string(),
+                },
+                _ => {
+                    AppError::ServerError(anyhow!("Could not store synonyms for entity `{}`", name))
+                }
+            })
+    }
+
+    fn synonyms(&self, name: &str) -> Result<BTreeMap<String, Vec<SynonymSet>>, AppError> {
+        self.inner
+            .synonyms(name)
+            .inspect_err(|err| error!("Could not read synonyms: {}", err))
+            .map_err(|_| anyhow!("Could not read synonyms for entity `{}`", name).into())
     }
 
     fn create_index(&self, name: &str, input: CreateIndexInput) -> Result<(), AppError> {
@@ -123,6 +202,8 @@ impl App {
                 name: create_index.name,
                 descriptor: TypeDescriptor::String(StringTypeDescriptor {
                     term: create_index.term,
+                    analyzer: create_index.analyzer,
+                    trigram: create_index.trigram,
                 }),
             },
             CreateIndexInput::Numeric(create_index) => CreateFieldIndex {
@@ -149,7 +230,7 @@ impl App {
 #[derive(Error, Debug)]
 enum AppError {
     #[error("filter query is not valid")]
-    InvalidFilterQuery,
+    InvalidFilterQuery(Box<ParseErrorResponse>),
     #[error("entity already exists")]
     EntityAlreadyExists { message: String },
     #[error("request is not valid")]
@@ -158,15 +239,34 @@ enum AppError {
     ServerError(#[from] anyhow::Error),
 }
 
+impl AppError {
+    fn invalid_filter_query(error: ParseError, query: &str) -> Self {
+        AppError::InvalidFilterQuery(Box::new(ParseErrorResponse {
+            code: error.code(),
+            message: error.to_string(),
+            span: error.span().copied(),
+            expected: error.expected().to_vec(),
+            pointer: error.render(query),
+        }))
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParseErrorResponse {
+    code: &'static str,
+    message: String,
+    span: Option<ErrorSpan>,
+    expected: Vec<String>,
+    pointer: String,
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         match self {
-            AppError::InvalidFilterQuery => Response::builder()
-                .status(StatusCode::BAD_REQUEST)
-                .body(Body::new(
-                    "Filter query is invalid or could not be parsed.".to_string(),
-                ))
-                .unwrap(),
+            AppError::InvalidFilterQuery(error) => {
+                (StatusCode::BAD_REQUEST, Json(error)).into_response()
+            }
             AppError::EntityAlreadyExists { message } => Response::builder()
                 .status(StatusCode::CONFLICT)
                 .body(Body::new(format!("Conflict: \"{}\"", message)))
@@ -197,9 +297,17 @@ async fn main() -> Result<(), AppError> {
         .route("/deltas/{entity_name}", post(bulk_add_deltas))
         // Index endpoints
         .route("/indices/{entity_name}", put(create_index))
+        .route(
+            "/synonyms/{entity_name}",
+            get(read_synonyms).put(store_synonyms),
+        )
         // Search endpoints
         .route("/options", post(options))
+        .route("/options/json", post(options_json))
         .route("/search", post(query))
+        .route("/search/json", post(query_json))
+        .route("/suggest", post(suggest))
+        .route("/similar", post(similar))
         .with_state(search_engine);
 
     info!("delta-search is running...");
@@ -228,6 +336,10 @@ struct BulkUpsertEntity {
 struct DataItemExternal {
     id: DataItemId,
     fields: DataItemFieldsExternal,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    highlight: Option<BTreeMap<String, Highlight>>,
 }
 
 impl DataItemExternal {
@@ -235,8 +347,20 @@ impl DataItemExternal {
         DataItemExternal {
             id: item.id,
             fields: DataItemFieldsExternal::new(item.fields),
+            score: None,
+            highlight: None,
         }
     }
+
+    fn with_score(mut self, score: Option<f64>) -> Self {
+        self.score = score;
+        self
+    }
+
+    fn with_highlight(mut self, highlight: Option<BTreeMap<String, Highlight>>) -> Self {
+        self.highlight = highlight;
+        self
+    }
 }
 
 async fn bulk_upsert_entity(
@@ -289,7 +413,7 @@ impl DeltaChangeInput {
 
                 Ok(FieldValue::Array(values))
             }
-            FieldValueExternal::Map(_) => Err(AppError::InvalidRequest {
+            FieldValueExternal::Map(_) | FieldValueExternal::Null => Err(AppError::InvalidRequest {
                 message: format!("Delta field value is invalid for field name {}. Only literals and arrays are allowed.", name),
             }),
         }
@@ -340,6 +464,75 @@ async fn options(
     Ok(Json(options))
 }
 
+async fn options_json(
+    State(search): State<App>,
+    Json(query): Json<Query>,
+) -> Result<Json<Vec<FilterOption>>, AppError> {
+    let options = search.options_json(query)?;
+    Ok(Json(options))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoreSynonymsInput {
+    field: String,
+    synonyms: Vec<SynonymSet>,
+}
+
+async fn store_synonyms(
+    State(search): State<App>,
+    Path(name): Path<String>,
+    Json(input): Json<StoreSynonymsInput>,
+) -> Result<Json<()>, AppError> {
+    search.store_synonyms(&name, input)?;
+    Ok(Json(()))
+}
+
+async fn read_synonyms(
+    State(search): State<App>,
+    Path(name): Path<String>,
+) -> Result<Json<BTreeMap<String, Vec<SynonymSet>>>, AppError> {
+    let synonyms = search.synonyms(&name)?;
+    Ok(Json(synonyms))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SuggestInput {
+    query: String,
+    field: String,
+    prefix: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+async fn suggest(
+    State(search): State<App>,
+    Json(input): Json<SuggestInput>,
+) -> Result<Json<Vec<Suggestion>>, AppError> {
+    let suggestions = search.suggest(input)?;
+    Ok(Json(suggestions))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimilarInput {
+    query: String,
+    id: DataItemId,
+    #[serde(default)]
+    fields: Vec<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+async fn similar(
+    State(search): State<App>,
+    Json(input): Json<SimilarInput>,
+) -> Result<Json<Vec<DataItemExternal>>, AppError> {
+    let items = search.similar(input)?;
+    Ok(Json(items))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "type")]
@@ -355,6 +548,10 @@ enum CreateIndexInput {
 struct CreateStringIndexTypeInput {
     name: String,
     term: bool,
+    #[serde(default)]
+    analyzer: Analyzer,
+    #[serde(default)]
+    trigram: bool,
 }
 
 #[derive(Deserialize)]
@@ -394,12 +591,85 @@ struct QueryInput {
 #[serde(rename_all = "camelCase")]
 struct QueryResponse {
     data: Vec<DataItemExternal>,
+    total: u64,
+    start: usize,
+    size: usize,
+    has_more: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<AggregationGroupExternal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plan: Option<QueryPlan>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<SpellingSuggestion>,
+}
+
+impl QueryResponse {
+    fn from_result(result: QueryResult) -> Self {
+        let mut scores = result.scores.into_iter().flatten();
+        let mut highlights = result.highlights.into_iter().flatten();
+
+        QueryResponse {
+            data: result
+                .items
+                .into_iter()
+                .map(|item| {
+                    DataItemExternal::from_item(item)
+                        .with_score(scores.next())
+                        .with_highlight(highlights.next())
+                })
+                .collect(),
+            total: result.total,
+            start: result.start,
+            size: result.size,
+            has_more: result.has_more,
+            groups: result
+                .groups
+                .into_iter()
+                .map(AggregationGroupExternal::from_group)
+                .collect(),
+            plan: result.plan,
+            suggestions: result.suggestions,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AggregationGroupExternal {
+    key: Option<FieldValueExternal>,
+    count: u64,
+    values: IndexMap<String, Option<FieldValueExternal>>,
+}
+
+impl AggregationGroupExternal {
+    fn from_group(group: AggregationGroup) -> Self {
+        AggregationGroupExternal {
+            key: group.key.as_ref().map(FieldValueExternal::from),
+            count: group.count,
+            values: group
+                .values
+                .into_iter()
+                .map(|aggregation| {
+                    let value = aggregation.value.as_ref().map(FieldValueExternal::from);
+                    (aggregation.name, value)
+                })
+                .collect(),
+        }
+    }
 }
 
 async fn query(
     State(search): State<App>,
     Json(input): Json<QueryInput>,
 ) -> Result<Json<QueryResponse>, AppError> {
-    let data = search.query(input)?;
-    Ok(Json(QueryResponse { data }))
+    let response = search.query(input)?;
+    Ok(Json(response))
+}
+
+async fn query_json(
+    State(search): State<App>,
+    Json(query): Json<Query>,
+) -> Result<Json<QueryResponse>, AppError> {
+    let response = search.query_json(query)?;
+    Ok(Json(response))
 }
@@ -1,35 +1,359 @@
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
 
+use ordered_float::OrderedFloat;
+use pest::error::{ErrorVariant, LineColLocation};
 use pest::iterators::Pair;
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest::Parser;
-use roaring::RoaringBitmap;
+use roaring::{MultiOps, RoaringBitmap};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::Date;
 
-use crate::data::{parse_date, DataItem, DataItemId, FieldValue};
-use crate::index::{FilterError, Index};
-use crate::storage::{position_to_id, EntityIndices, EntityStorage, StorageError};
+use crate::data::{parse_date, DataItem, DataItemId, FieldValue, FieldValueExternal};
+use crate::index::{Correction, FilterError, Index, IndexError, Pattern};
+use crate::storage::{id_to_position, position_to_id, EntityIndices, EntityStorage, StorageError};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct FilterOption {
     pub field: String,
     pub values: BTreeMap<String, u64>,
+    /// Amount of items without a value for the field.
+    pub missing: u64,
 }
 
 impl FilterOption {
-    pub(crate) fn new(field: String, values: BTreeMap<String, u64>) -> Self {
-        FilterOption { field, values }
+    pub(crate) fn new(field: String, values: BTreeMap<String, u64>, missing: u64) -> Self {
+        FilterOption {
+            field,
+            values,
+            missing,
+        }
+    }
+}
+
+/// A completion of a prefix, together with the amount of items containing it.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub value: String,
+    pub count: u64,
+}
+
+impl Suggestion {
+    pub(crate) fn new(value: String, count: u64) -> Self {
+        Suggestion { value, count }
+    }
+}
+
+/// An item similar to another one, together with how similar they are.
+#[derive(Debug, PartialEq)]
+pub struct SimilarItem {
+    pub item: DataItem,
+    pub score: f64,
+}
+
+/// Maximum amount of spelling suggestions returned for each searched term.
+const MAX_SPELLING_SUGGESTIONS: usize = 5;
+
+/// A spelling correction of a searched term, built from the terms indexed for a field.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpellingSuggestion {
+    pub field: String,
+    /// The searched term missing from the field, as analyzed.
+    pub term: String,
+    /// The indexed term that may have been meant instead.
+    pub suggestion: String,
+    /// Edit distance between the searched and the suggested term.
+    pub distance: u32,
+    /// Amount of items containing the suggested term.
+    pub count: u64,
+}
+
+impl SpellingSuggestion {
+    fn from_correction(field: &str, correction: Correction) -> Self {
+        SpellingSuggestion {
+            field: field.to_string(),
+            term: correction.term,
+            suggestion: correction.value,
+            distance: correction.distance,
+            count: correction.count,
+        }
+    }
+}
+
+/// Amount of characters of a value kept around the highlighted words in a snippet.
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+/// The words of a field value matching the searched terms.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Highlight {
+    /// Character offsets of the matching words, in the order they appear in the value.
+    pub spans: Vec<HighlightSpan>,
+    /// Excerpt of the value around the matching words, which are wrapped in `<em>` tags.
+    pub snippet: String,
+}
+
+impl Highlight {
+    fn new(value: &str, mut spans: Vec<Range<usize>>) -> Self {
+        spans.sort_by_key(|span| span.start);
+        spans.dedup();
+
+        let snippet = build_snippet(value, &spans);
+        Highlight {
+            spans: spans
+                .into_iter()
+                .map(|span| HighlightSpan {
+                    start: span.start,
+                    end: span.end,
+                })
+                .collect(),
+            snippet,
+        }
+    }
+}
+
+/// Character offsets of a highlighted word, with an exclusive `end`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct HighlightSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Build an excerpt of a value from the context before the first span until the context
+/// after the last one, without splitting words. The spans are wrapped in `<em>` tags and
+/// the rest of the value is escaped, so that the snippet can be rendered as HTML.
+fn build_snippet(value: &str, spans: &[Range<usize>]) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let (Some(first), Some(last)) = (spans.first(), spans.last()) else {
+        return String::new();
+    };
+
+    let mut start = first.start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    if start > 0 {
+        start = (start..first.start)
+            .find(|index| chars[index - 1].is_whitespace())
+            .unwrap_or(first.start);
+    }
+
+    let mut end = (last.end + SNIPPET_CONTEXT_CHARS).min(chars.len());
+    if end < chars.len() {
+        end = (last.end..=end)
+            .rev()
+            .find(|index| chars[*index].is_whitespace())
+            .unwrap_or(last.end);
+    }
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+
+    let mut spans = spans.iter().peekable();
+    for (index, c) in chars.iter().enumerate().take(end).skip(start) {
+        if spans.peek().is_some_and(|span| span.start == index) {
+            snippet.push_str("<em>");
+        }
+
+        match c {
+            '<' => snippet.push_str("&lt;"),
+            '>' => snippet.push_str("&gt;"),
+            '&' => snippet.push_str("&amp;"),
+            '"' => snippet.push_str("&quot;"),
+            c => snippet.push(*c),
+        }
+
+        if spans.peek().is_some_and(|span| span.end == index + 1) {
+            snippet.push_str("</em>");
+            spans.next();
+        }
+    }
+
+    if end < chars.len() {
+        snippet.push('…');
+    }
+
+    snippet.trim().to_string()
+}
+
+/// The result of a query execution, including the items of the requested page and
+/// information about the total amount of items matching the query.
+#[derive(Debug, PartialEq)]
+pub struct QueryResult {
+    /// Items of the requested page.
+    pub items: Vec<DataItem>,
+    /// Total amount of items matching the query, regardless of the pagination.
+    pub total: u64,
+    /// Position of the first returned item within all the matching items.
+    pub start: usize,
+    /// Amount of returned items.
+    pub size: usize,
+    /// Whether there are more matching items after the returned page.
+    pub has_more: bool,
+    /// Aggregated values of the matching items, if any aggregation was requested.
+    pub groups: Vec<AggregationGroup>,
+    /// Plan of the execution, if the query was explained.
+    pub plan: Option<QueryPlan>,
+    /// Relevance score of each returned item, in the same order as `items`. Only present
+    /// if the query is ranked, either by filtering on terms or by sorting on `_score`.
+    pub scores: Option<Vec<f64>>,
+    /// Spelling corrections for the searched terms, only present if the query searches
+    /// for terms and no item matches it.
+    pub suggestions: Vec<SpellingSuggestion>,
+    /// Highlighted words of each returned item, in the same order as `items`, for each
+    /// highlighted field with matching words. Only present if highlighting was requested.
+    pub highlights: Option<Vec<BTreeMap<String, Highlight>>>,
+}
+
+impl QueryResult {
+    pub(crate) fn empty() -> Self {
+        QueryResult {
+            items: Vec::new(),
+            total: 0,
+            start: 0,
+            size: 0,
+            has_more: false,
+            groups: Vec::new(),
+            plan: None,
+            scores: None,
+            suggestions: Vec::new(),
+            highlights: None,
+        }
+    }
+}
+
+/// The plan of a query execution, describing how each of its steps was executed.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPlan {
+    pub entity: String,
+    /// Time spent reading the indices, including applying the deltas.
+    pub read_elapsed_micros: u64,
+    /// Deltas applied to the indices, if the query is scoped.
+    pub deltas: Option<DeltaPlan>,
+    /// Filter tree as evaluated, if the query is filtered.
+    pub filter: Option<FilterPlan>,
+    pub sort: SortPlan,
+    /// Amount of items matching the query.
+    pub total: u64,
+    /// Time spent executing the whole query.
+    pub elapsed_micros: u64,
+}
+
+impl QueryPlan {
+    /// Plan of a query over an entity without items, which reads nothing.
+    pub(crate) fn empty(entity: String) -> Self {
+        QueryPlan {
+            entity,
+            read_elapsed_micros: 0,
+            deltas: None,
+            filter: None,
+            sort: SortPlan {
+                strategy: SortStrategy::Id,
+                keys: Vec::new(),
+                elapsed_micros: 0,
+            },
+            total: 0,
+            elapsed_micros: 0,
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaPlan {
+    pub branch: Option<u32>,
+    pub date: String,
+    /// Fields whose indices were modified by the deltas.
+    pub fields: Vec<String>,
+    /// Amount of items affected by the deltas.
+    pub affected_items: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterNode {
+    And,
+    Or,
+    Not,
+    Single,
+}
+
+/// A node of the filter tree, with the index answering it (for single filters), the
+/// amount of matching items and the time spent evaluating it, including its children.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterPlan {
+    pub node: FilterNode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    pub cardinality: u64,
+    pub elapsed_micros: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<FilterPlan>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortStrategy {
+    /// Items are returned in the order of their ID.
+    Id,
+    /// Items are grouped by the values of the sorted indices, refining the groups with
+    /// every sort key.
+    Indices,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SortPlan {
+    pub strategy: SortStrategy,
+    pub keys: Vec<String>,
+    pub elapsed_micros: u64,
+}
+
+fn as_micros(duration: Duration) -> u64 {
+    duration.as_micros().try_into().unwrap_or(u64::MAX)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct DeltaScope {
     pub(crate) branch: Option<u32>,
+    #[serde(with = "iso_date")]
     pub(crate) date: Date,
 }
 
+/// (De)serialize dates using their ISO 8601 representation (e.g. "2020-01-01"), the same
+/// representation used in the text query language.
+mod iso_date {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use time::Date;
+
+    use crate::data::parse_date;
+
+    pub(super) fn serialize<S>(date: &Date, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(date)
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Date, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        parse_date(&value).map_err(serde::de::Error::custom)
+    }
+}
+
 impl DeltaScope {
     pub fn new(branch: Option<u32>, date: Date) -> Self {
         Self { branch, date }
@@ -97,37 +421,424 @@ impl QueryIndices {
             }
             CompositeFilter::Not(filter) => {
                 let result = self.execute_filter(filter)?;
-                FilterResult::new(&self.indices.all - result.hits)
+                FilterResult::new(self.negate(filter, &result.hits)?)
+            }
+            CompositeFilter::Single(filter) => FilterResult::new(self.execute_single(filter)?),
+        };
+
+        Ok(result)
+    }
+
+    /// Negate the hits of a filter. As in SQL, items without a value for the field of a
+    /// single filter (e.g. `score != 5` or `score NOT IN [5, 10]`) match neither the filter
+    /// nor its negation, except for `IS NULL` and `EXISTS`, which match on missing values.
+    fn negate(
+        &self,
+        filter: &CompositeFilter,
+        hits: &RoaringBitmap,
+    ) -> Result<RoaringBitmap, QueryError> {
+        let mut negated = &self.indices.all - hits;
+
+        if let CompositeFilter::Single(filter) = filter {
+            let is_null_check = matches!(
+                filter.operation,
+                FilterOperation::IsNull | FilterOperation::Exists
+            );
+
+            if let (false, Some(index)) = (is_null_check, self.get(&filter.name)) {
+                negated &= index.filter(&FilterOperation::Exists)?;
+            }
+        }
+
+        Ok(negated)
+    }
+
+    /// Execute a filter on a single field. Filters on the `_all` pseudo-field are executed
+    /// on every field with a term index, matching the items found in any of them.
+    fn execute_single(&self, filter: &Filter) -> Result<RoaringBitmap, QueryError> {
+        if filter.name == ALL_FIELDS {
+            if !filter.operation.is_term() {
+                return Err(QueryError::Filter(
+                    FilterError::UnsupportedAllFieldsOperation,
+                ));
+            }
+
+            let mut hits = RoaringBitmap::new();
+            for (_, index) in self.term_indices() {
+                hits |= index.filter(&filter.operation)?;
+            }
+
+            return Ok(hits);
+        }
+
+        let Some(index) = self.get(&filter.name) else {
+            return Err(QueryError::Filter(FilterError::MissingIndex(
+                filter.name.to_string(),
+            )));
+        };
+
+        Ok(index.filter(&filter.operation)?)
+    }
+
+    /// Indices of the fields with a term index, which are searched by the `_all` pseudo-field.
+    fn term_indices(&self) -> impl Iterator<Item = (&String, &Index)> {
+        self.indices
+            .field_indices
+            .iter()
+            .filter(|(_, index)| index.has_term())
+    }
+
+    /// Execute the filter as `execute_filter`, while describing how each node of the filter
+    /// tree was evaluated.
+    fn explain_filter(
+        &self,
+        filter: &CompositeFilter,
+    ) -> Result<(FilterResult, FilterPlan), QueryError> {
+        let started = Instant::now();
+
+        let (result, node, index, children) = match filter {
+            CompositeFilter::And(filters) | CompositeFilter::Or(filters) => {
+                let is_and = matches!(filter, CompositeFilter::And(_));
+                let mut result: Option<FilterResult> = None;
+                let mut children = Vec::with_capacity(filters.len());
+
+                for filter in filters {
+                    let (inner, plan) = self.explain_filter(filter)?;
+                    children.push(plan);
+
+                    let next = match result {
+                        Some(current) if is_and => current.and(inner),
+                        Some(current) => current.or(inner),
+                        None => inner,
+                    };
+
+                    result = Some(next);
+                }
+
+                let node = if is_and {
+                    FilterNode::And
+                } else {
+                    FilterNode::Or
+                };
+
+                (
+                    result.unwrap_or_else(FilterResult::empty),
+                    node,
+                    None,
+                    children,
+                )
+            }
+            CompositeFilter::Not(filter) => {
+                let (result, plan) = self.explain_filter(filter)?;
+                let result = FilterResult::new(self.negate(filter, &result.hits)?);
+
+                (result, FilterNode::Not, None, vec![plan])
             }
             CompositeFilter::Single(filter) => {
-                let Some(index) = self.get(&filter.name) else {
-                    return Err(QueryError::Filter(FilterError::MissingIndex(
-                        filter.name.to_string(),
-                    )));
+                let hits = self.execute_single(filter)?;
+
+                // Term based operations are answered by the term index of string indices,
+                // including the ones searched through the `_all` pseudo-field
+                let index_name = match self.get(&filter.name) {
+                    Some(index)
+                        if !matches!(index, Index::String(_)) || !filter.operation.is_term() =>
+                    {
+                        index.type_name().to_string()
+                    }
+                    _ => "term".to_string(),
                 };
 
-                let hits = index.filter(&filter.operation)?;
+                (
+                    FilterResult::new(hits),
+                    FilterNode::Single,
+                    Some(index_name),
+                    Vec::new(),
+                )
+            }
+        };
+
+        let plan = FilterPlan {
+            node,
+            filter: match filter {
+                CompositeFilter::Single(filter) => Some(filter.to_string()),
+                _ => None,
+            },
+            index,
+            cardinality: result.hits.len(),
+            elapsed_micros: as_micros(started.elapsed()),
+            children,
+        };
+
+        Ok((result, plan))
+    }
+
+    /// Sort the items by each of the provided sorts, where every sort refines the groups of
+    /// equal values left by the previous one. Items that are still equal after all the sorts
+    /// are ordered by their position, and therefore by their ID.
+    fn execute_sort(
+        &self,
+        items: &RoaringBitmap,
+        sorts: &[Sort],
+        scores: &HashMap<u32, f64>,
+    ) -> Result<Vec<u32>, QueryError> {
+        let mut groups = vec![items.clone()];
+
+        for sort in sorts {
+            let index = if sort.by == SCORE_FIELD {
+                None
+            } else {
+                let index = self.get(&sort.by).ok_or_else(|| {
+                    QueryError::Filter(FilterError::MissingIndex(sort.by.to_string()))
+                })?;
+                Some(index)
+            };
+
+            let mut refined = Vec::with_capacity(groups.len());
+            for group in groups {
+                if group.len() <= 1 {
+                    refined.push(group);
+                } else if let Some(index) = index {
+                    refined.extend(index.sort(&group, &sort.direction, &sort.nulls));
+                } else {
+                    refined.extend(sort_by_score(&group, scores, &sort.direction));
+                }
+            }
+
+            groups = refined;
+        }
+
+        Ok(groups.iter().flat_map(|group| group.iter()).collect())
+    }
+
+    /// Compute the relevance score of the items for the term filters of the query, by adding up
+    /// the BM25 score of each of them multiplied by the boost of its field. Negated filters do
+    /// not contribute to the score.
+    fn execute_scores(
+        &self,
+        filter: &CompositeFilter,
+        items: &RoaringBitmap,
+        boosts: &[Boost],
+    ) -> HashMap<u32, f64> {
+        let mut scores = HashMap::new();
+
+        for filter in filter.scored_filters() {
+            for (field, index) in self.filter_indices(filter) {
+                let weight = boosts
+                    .iter()
+                    .find(|boost| &boost.field == field)
+                    .map_or(1.0, |boost| boost.weight);
+
+                let mut field_scores = HashMap::new();
+                index.score(&filter.operation, items, &mut field_scores);
+
+                for (position, score) in field_scores {
+                    *scores.entry(position).or_default() += weight * score;
+                }
+            }
+        }
+
+        scores
+    }
+
+    /// Find spelling corrections for the terms searched by the filter, ranked by edit
+    /// distance and then by the amount of items containing them.
+    fn execute_corrections(&self, filter: &CompositeFilter) -> Vec<SpellingSuggestion> {
+        let mut suggestions = Vec::new();
+
+        for filter in filter.scored_filters() {
+            for (field, index) in self.filter_indices(filter) {
+                suggestions.extend(
+                    index
+                        .correct(&filter.operation)
+                        .into_iter()
+                        .map(|correction| SpellingSuggestion::from_correction(field, correction)),
+                );
+            }
+        }
+
+        suggestions.sort_by(|first, second| {
+            first
+                .distance
+                .cmp(&second.distance)
+                .then_with(|| second.count.cmp(&first.count))
+                .then_with(|| first.suggestion.cmp(&second.suggestion))
+                .then_with(|| first.field.cmp(&second.field))
+        });
+
+        // Keep only the best suggestions of each searched term
+        let mut term_counts: HashMap<String, usize> = HashMap::new();
+        suggestions.retain(|suggestion| {
+            let count = term_counts.entry(suggestion.term.clone()).or_default();
+            *count += 1;
+            *count <= MAX_SPELLING_SUGGESTIONS
+        });
+
+        suggestions
+    }
+
+    /// Highlight the words of the item matching the terms searched by the filter, for each
+    /// of the given fields. The `_all` pseudo-field refers to every field with a term index.
+    /// Fields not read with the item, e.g. due to a projection, are read from the index.
+    fn execute_highlight(
+        &self,
+        filter: &CompositeFilter,
+        fields: &[String],
+        item: &DataItem,
+    ) -> BTreeMap<String, Highlight> {
+        let filters = filter.scored_filters();
+        let position = id_to_position(item.id);
+        let mut highlights = BTreeMap::new();
+
+        for (field, index) in self.fields_indices(fields) {
+            let value = match item.fields.get(field) {
+                Some(value) => Cow::Borrowed(value),
+                None => match index.get_value(position) {
+                    Some(value) => Cow::Owned(value),
+                    None => continue,
+                },
+            };
+            let Some(value) = value.as_string() else {
+                continue;
+            };
+
+            let spans: Vec<Range<usize>> = filters
+                .iter()
+                .filter(|filter| &filter.name == field || filter.name == ALL_FIELDS)
+                .flat_map(|filter| index.highlight(&filter.operation, position, value))
+                .collect();
 
-                FilterResult::new(hits)
+            if !spans.is_empty() {
+                highlights.insert(field.clone(), Highlight::new(value, spans));
             }
+        }
+
+        highlights
+    }
+
+    /// Indices of the given fields, which are all the indices with a term index if the
+    /// `_all` pseudo-field is given. Fields without an index are skipped.
+    fn fields_indices(&self, fields: &[String]) -> Vec<(&String, &Index)> {
+        if fields.iter().any(|field| field == ALL_FIELDS) {
+            self.term_indices().collect()
+        } else {
+            fields
+                .iter()
+                .filter_map(|field| self.indices.field_indices.get_key_value(field))
+                .collect()
+        }
+    }
+
+    /// Indices searched by a single filter, which are all the indices with a term index
+    /// for the `_all` pseudo-field.
+    fn filter_indices(&self, filter: &Filter) -> Vec<(&String, &Index)> {
+        if filter.name == ALL_FIELDS {
+            self.term_indices().collect()
+        } else {
+            self.indices
+                .field_indices
+                .get_key_value(&filter.name)
+                .into_iter()
+                .collect()
+        }
+    }
+
+    /// Aggregate the items by the values of the `group_by` field, or as a single group if no
+    /// field is provided. Items without a value for the `group_by` field are collected in
+    /// a group without key.
+    fn execute_aggregation(
+        &self,
+        items: &RoaringBitmap,
+        group_by: Option<&String>,
+        aggregations: &[Aggregation],
+    ) -> Result<Vec<AggregationGroup>, QueryError> {
+        let groups = match group_by {
+            Some(field) => {
+                let index = self.get(field).ok_or_else(|| {
+                    QueryError::Filter(FilterError::MissingIndex(field.to_string()))
+                })?;
+
+                let mut groups: Vec<(Option<FieldValue>, RoaringBitmap)> = index
+                    .groups(items)
+                    .into_iter()
+                    .map(|(key, group)| (Some(key), group))
+                    .collect();
+
+                let missing = items - groups.iter().map(|(_, group)| group).union();
+                if !missing.is_empty() {
+                    groups.push((None, missing));
+                }
+
+                groups
+            }
+            None => vec![(None, items.clone())],
         };
 
+        let mut result = Vec::with_capacity(groups.len());
+
+        for (key, group) in groups {
+            let mut values = Vec::with_capacity(aggregations.len());
+
+            for aggregation in aggregations {
+                let value = match &aggregation.field {
+                    Some(field) => {
+                        let index = self.get(field).ok_or_else(|| {
+                            QueryError::Filter(FilterError::MissingIndex(field.to_string()))
+                        })?;
+
+                        index.aggregate(&aggregation.function, &group)?
+                    }
+                    None => Some(FieldValue::Integer(group.len())),
+                };
+
+                values.push(AggregationValue {
+                    name: aggregation.to_string(),
+                    value,
+                });
+            }
+
+            result.push(AggregationGroup {
+                key,
+                count: group.len(),
+                values,
+            });
+        }
+
         Ok(result)
     }
 
-    fn execute_sort(&self, items: &RoaringBitmap, sort: &Sort) -> Result<Vec<u32>, QueryError> {
-        let index = self
-            .get(&sort.by)
-            .ok_or_else(|| QueryError::Filter(FilterError::MissingIndex(sort.by.to_string())))?;
+    /// Build the data items for the given IDs using only the values stored in the indices
+    /// of the projected fields.
+    fn read_projected<'a, T>(&self, ids: T, projection: &[String]) -> Vec<DataItem>
+    where
+        T: Iterator<Item = &'a DataItemId>,
+    {
+        let mut items = Vec::new();
+
+        for id in ids {
+            let position = id_to_position(*id);
+            let mut fields = BTreeMap::new();
+
+            for field in projection {
+                if let Some(value) = self.get(field).and_then(|index| index.get_value(position)) {
+                    fields.insert(field.clone(), value);
+                }
+            }
+
+            items.push(DataItem::new(*id, fields));
+        }
 
-        Ok(index.sort(items, &sort.direction))
+        items
     }
 
     fn compute_filter_options(&self, hits: RoaringBitmap) -> Vec<FilterOption> {
         let mut filter_options = Vec::new();
 
         for (field, index) in &self.indices.field_indices {
-            filter_options.push(FilterOption::new(field.to_string(), index.counts(&hits)));
+            filter_options.push(FilterOption::new(
+                field.to_string(),
+                index.counts(&hits),
+                index.count_missing(&hits),
+            ));
         }
 
         filter_options
@@ -148,11 +859,17 @@ impl OptionsQueryExecution {
 
     pub fn parse_query(query: &str) -> Result<Self, ParseError> {
         let parsed = QueryParser::parse_query(query)?;
-        Ok(OptionsQueryExecution {
+        Ok(OptionsQueryExecution::from_query(parsed))
+    }
+
+    /// Create an execution from a query definition, e.g. deserialized from JSON. Only
+    /// the entity, filter and scope of the query are considered.
+    pub fn from_query(parsed: Query) -> Self {
+        OptionsQueryExecution {
             entity: parsed.entity,
             filter: parsed.filter,
             scope: parsed.scope,
-        })
+        }
     }
 
     pub fn for_entity(mut self, entity: String) -> Self {
@@ -190,87 +907,464 @@ impl OptionsQueryExecution {
     }
 }
 
-#[derive(Default)]
-pub struct QueryExecution {
+/// The items an execution searches within: the items of an entity matching an optional
+/// filter, as they are in an optional delta scope.
+#[derive(Debug)]
+pub(crate) struct QueryTarget {
     pub(crate) entity: String,
     filter: Option<CompositeFilter>,
-    sort: Option<Sort>,
     scope: Option<DeltaScope>,
-    pagination: Pagination,
-    ref_fields: Vec<String>,
 }
 
-impl QueryExecution {
-    pub fn new() -> Self {
-        QueryExecution::default()
+impl QueryTarget {
+    fn new(entity: String) -> Self {
+        QueryTarget {
+            entity,
+            filter: None,
+            scope: None,
+        }
     }
 
-    pub fn parse_query(query: &str) -> Result<Self, ParseError> {
+    /// Parse a query, where only its entity, filter and scope are considered.
+    fn parse_query(query: &str) -> Result<Self, ParseError> {
         let parsed = QueryParser::parse_query(query)?;
 
-        let mut ref_fields = Vec::new();
-        if let Some(filter) = parsed.filter.as_ref() {
-            ref_fields.extend(filter.get_referenced_fields());
-        }
-        if let Some(sort) = parsed.sort.as_ref() {
-            ref_fields.extend(sort.get_referenced_fields());
-        }
-
-        Ok(QueryExecution {
+        Ok(QueryTarget {
             entity: parsed.entity,
             filter: parsed.filter,
-            sort: parsed.sort,
             scope: parsed.scope,
-            pagination: parsed.pagination,
-            ref_fields,
         })
     }
 
-    pub fn for_entity(mut self, entity: String) -> Self {
-        self.entity = entity;
-        self
+    /// Read the indices of the given fields and of the fields of the filter, in the scope,
+    /// returning them together with the items matching the filter.
+    fn read(
+        &self,
+        mut fields: Vec<String>,
+        storage: &EntityStorage,
+    ) -> Result<(QueryIndices, RoaringBitmap), QueryError> {
+        if let Some(filter) = self.filter.as_ref() {
+            fields.extend(filter.get_referenced_fields());
+        }
+
+        let fields = read_fields(fields, storage);
+        let indices = match &self.scope {
+            Some(scope) => storage.read_indices_in(scope, &fields),
+            None => storage.read_current_indices(&fields),
+        }?;
+
+        let indices = QueryIndices::new(indices);
+
+        let items = match self.filter.as_ref() {
+            Some(filter) => indices.execute_filter(filter)?.hits,
+            None => indices.indices.all.clone(),
+        };
+
+        Ok((indices, items))
     }
+}
 
-    pub fn with_filter(mut self, filter: CompositeFilter) -> Self {
-        self.ref_fields.append(&mut filter.get_referenced_fields());
-        self.filter = Some(filter);
-        self
+/// Default amount of suggestions returned when completing a prefix.
+pub const DEFAULT_SUGGEST_LIMIT: usize = 10;
+
+/// Completes a prefix with the most frequent values or terms of a field, within the
+/// items matching an optional filter.
+#[derive(Debug)]
+pub struct SuggestExecution {
+    pub(crate) target: QueryTarget,
+    field: String,
+    prefix: String,
+    limit: usize,
+}
+
+impl SuggestExecution {
+    pub fn new(entity: String, field: &str, prefix: &str, limit: usize) -> Self {
+        SuggestExecution::for_target(QueryTarget::new(entity), field, prefix, limit)
     }
 
-    pub fn with_sort(mut self, sort: Sort) -> Self {
-        self.ref_fields.append(&mut sort.get_referenced_fields());
-        self.sort = Some(sort);
+    /// Create an execution completing the prefix for the field within the items matching
+    /// the filter of the query.
+    pub fn parse_query(query: &str, field: &str, prefix: &str) -> Result<Self, ParseError> {
+        let target = QueryTarget::parse_query(query)?;
+        Ok(SuggestExecution::for_target(
+            target,
+            field,
+            prefix,
+            DEFAULT_SUGGEST_LIMIT,
+        ))
+    }
+
+    fn for_target(target: QueryTarget, field: &str, prefix: &str, limit: usize) -> Self {
+        SuggestExecution {
+            target,
+            field: field.to_string(),
+            prefix: prefix.to_string(),
+            limit,
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
         self
     }
 
-    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
-        self.pagination = pagination;
+    pub fn with_filter(mut self, filter: CompositeFilter) -> Self {
+        self.target.filter = Some(filter);
         self
     }
 
     pub fn with_scope(mut self, scope: DeltaScope) -> Self {
-        self.scope = Some(scope);
+        self.target.scope = Some(scope);
         self
     }
 
-    pub fn run(self, storage: &EntityStorage) -> Result<Vec<DataItem>, QueryError> {
-        // Read indices for the referenced fields in the query
-        let indices = match &self.scope {
-            Some(scope) => storage.read_indices_in(scope, &self.ref_fields),
-            None => storage.read_current_indices(&self.ref_fields),
-        }?;
+    pub fn run(self, storage: &EntityStorage) -> Result<Vec<Suggestion>, QueryError> {
+        let (indices, items) = self.target.read(vec![self.field.clone()], storage)?;
 
-        let indices = QueryIndices::new(indices);
+        let index = indices
+            .get(&self.field)
+            .ok_or_else(|| QueryError::Filter(FilterError::MissingIndex(self.field.to_string())))?;
 
-        // Apply filter given the indices
-        let filter_result = if let Some(filter) = self.filter.as_ref() {
-            indices.execute_filter(filter)?
+        // Rank the completions by the amount of items containing them
+        let mut completions = index.suggest(&self.prefix, &items)?;
+        completions.sort_by(|(first, first_count), (second, second_count)| {
+            second_count
+                .cmp(first_count)
+                .then_with(|| first.cmp(second))
+        });
+
+        Ok(completions
+            .into_iter()
+            .take(self.limit)
+            .map(|(value, count)| Suggestion::new(value, count))
+            .collect())
+    }
+}
+
+/// Default amount of similar items returned.
+pub const DEFAULT_SIMILAR_LIMIT: usize = 10;
+
+/// Finds the items sharing the most distinctive terms with a given item in the term indices
+/// of the fields, within the items matching an optional filter. Shared terms are weighted
+/// by how rare they are among all the items, so that common terms barely count.
+#[derive(Debug)]
+pub struct SimilarExecution {
+    pub(crate) target: QueryTarget,
+    id: DataItemId,
+    fields: Vec<String>,
+    limit: usize,
+}
+
+impl SimilarExecution {
+    /// Create an execution comparing the given fields, or every field with a term index
+    /// if none is given.
+    pub fn new(entity: String, id: DataItemId, fields: &[String], limit: usize) -> Self {
+        SimilarExecution::for_target(QueryTarget::new(entity), id, fields, limit)
+    }
+
+    /// Create an execution finding the items similar to the given one among the items
+    /// matching the filter of the query.
+    pub fn parse_query(query: &str, id: DataItemId, fields: &[String]) -> Result<Self, ParseError> {
+        let target = QueryTarget::parse_query(query)?;
+        Ok(SimilarExecution::for_target(
+            target,
+            id,
+            fields,
+            DEFAULT_SIMILAR_LIMIT,
+        ))
+    }
+
+    fn for_target(target: QueryTarget, id: DataItemId, fields: &[String], limit: usize) -> Self {
+        let fields = if fields.is_empty() {
+            vec![ALL_FIELDS.to_string()]
         } else {
-            FilterResult::new(indices.indices.all.clone())
+            fields.to_vec()
+        };
+
+        SimilarExecution {
+            target,
+            id,
+            fields,
+            limit,
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: CompositeFilter) -> Self {
+        self.target.filter = Some(filter);
+        self
+    }
+
+    pub fn with_scope(mut self, scope: DeltaScope) -> Self {
+        self.target.scope = Some(scope);
+        self
+    }
+
+    pub fn run(self, storage: &EntityStorage) -> Result<Vec<SimilarItem>, QueryError> {
+        let (indices, items) = self.target.read(self.fields.clone(), storage)?;
+
+        if let Some(field) = self
+            .fields
+            .iter()
+            .find(|field| *field != ALL_FIELDS && indices.get(field).is_none())
+        {
+            return Err(QueryError::Filter(FilterError::MissingIndex(field.clone())));
+        }
+
+        let position = id_to_position(self.id);
+        if !indices.indices.all.contains(position) {
+            return Ok(Vec::new());
+        }
+
+        let mut scores = HashMap::new();
+        for (_, index) in indices.fields_indices(&self.fields) {
+            index.score_similar(position, &items, &mut scores);
+        }
+
+        // Rank the items by score, keeping the ones sharing at least a term
+        let mut ranked: Vec<(u32, f64)> = scores
+            .into_iter()
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        ranked.sort_by(|(first, first_score), (second, second_score)| {
+            second_score
+                .total_cmp(first_score)
+                .then_with(|| first.cmp(second))
+        });
+        ranked.truncate(self.limit);
+
+        let ids: Vec<DataItemId> = ranked
+            .iter()
+            .map(|(position, _)| position_to_id(*position))
+            .collect();
+        let scores: HashMap<DataItemId, f64> = ids
+            .iter()
+            .copied()
+            .zip(ranked.iter().map(|(_, score)| *score))
+            .collect();
+
+        let items = storage.read_multiple(ids.iter(), &indices.indices)?;
+
+        Ok(items
+            .into_iter()
+            .map(|item| SimilarItem {
+                score: scores[&item.id],
+                item,
+            })
+            .collect())
+    }
+}
+
+/// Fields whose indices are read to execute a query. The `_all` pseudo-field refers to
+/// every field with a term index.
+fn read_fields(mut fields: Vec<String>, storage: &EntityStorage) -> Vec<String> {
+    if fields.iter().any(|field| field == ALL_FIELDS) {
+        fields.extend(storage.term_fields());
+    }
+    fields
+}
+
+#[derive(Default)]
+pub struct QueryExecution {
+    pub(crate) entity: String,
+    filter: Option<CompositeFilter>,
+    sort: Vec<Sort>,
+    scope: Option<DeltaScope>,
+    pagination: Pagination,
+    projection: Option<Vec<String>>,
+    group_by: Option<String>,
+    aggregations: Vec<Aggregation>,
+    boosts: Vec<Boost>,
+    highlight: Vec<String>,
+    explain: bool,
+    ref_fields: Vec<String>,
+}
+
+impl QueryExecution {
+    pub fn new() -> Self {
+        QueryExecution::default()
+    }
+
+    pub fn parse_query(query: &str) -> Result<Self, ParseError> {
+        let parsed = QueryParser::parse_query(query)?;
+        Ok(QueryExecution::from_query(parsed))
+    }
+
+    /// Create an execution from a query definition, e.g. deserialized from JSON.
+    pub fn from_query(parsed: Query) -> Self {
+        let mut ref_fields = Vec::new();
+        if let Some(filter) = parsed.filter.as_ref() {
+            ref_fields.extend(filter.get_referenced_fields());
+        }
+        for sort in &parsed.sort {
+            ref_fields.extend(sort.get_referenced_fields());
+        }
+        if let Some(projection) = parsed.projection.as_ref() {
+            ref_fields.extend(projection.iter().cloned());
+        }
+        if let Some(group_by) = parsed.group_by.as_ref() {
+            ref_fields.push(group_by.clone());
+        }
+        for aggregation in &parsed.aggregations {
+            ref_fields.extend(aggregation.get_referenced_fields());
+        }
+        ref_fields.extend(parsed.highlight.iter().cloned());
+
+        QueryExecution {
+            entity: parsed.entity,
+            filter: parsed.filter,
+            sort: parsed.sort,
+            scope: parsed.scope,
+            pagination: parsed.pagination,
+            projection: parsed.projection,
+            group_by: parsed.group_by,
+            aggregations: parsed.aggregations,
+            boosts: parsed.boosts,
+            highlight: parsed.highlight,
+            explain: parsed.explain,
+            ref_fields,
+        }
+    }
+
+    pub fn for_entity(mut self, entity: String) -> Self {
+        self.entity = entity;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: CompositeFilter) -> Self {
+        self.ref_fields.append(&mut filter.get_referenced_fields());
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Add a sort to the query. Any following sort is used as a tie-breaker for the items
+    /// with equal values in the previous sorts.
+    pub fn with_sort(mut self, sort: Sort) -> Self {
+        self.ref_fields.append(&mut sort.get_referenced_fields());
+        self.sort.push(sort);
+        self
+    }
+
+    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    /// Only return the given fields for each of the resulting items.
+    pub fn with_projection(mut self, fields: Vec<String>) -> Self {
+        self.ref_fields.extend(fields.iter().cloned());
+        self.projection = Some(fields);
+        self
+    }
+
+    /// Group the resulting items by the values of the given field, for which the
+    /// aggregations are computed.
+    pub fn with_group_by(mut self, field: &str) -> Self {
+        self.ref_fields.push(field.to_string());
+        self.group_by = Some(field.to_string());
+        self
+    }
+
+    /// Add an aggregation computed over the resulting items of each group.
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.ref_fields
+            .append(&mut aggregation.get_referenced_fields());
+        self.aggregations.push(aggregation);
+        self
+    }
+
+    pub fn with_scope(mut self, scope: DeltaScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Weight the relevance score of a field when ranking the items.
+    pub fn with_boost(mut self, boost: Boost) -> Self {
+        self.boosts.push(boost);
+        self
+    }
+
+    /// Highlight the words of the given field matching the searched terms in each of the
+    /// resulting items.
+    pub fn with_highlight(mut self, field: &str) -> Self {
+        self.ref_fields.push(field.to_string());
+        self.highlight.push(field.to_string());
+        self
+    }
+
+    /// Execute the query and describe how it was executed, instead of only returning
+    /// the matching items.
+    pub fn explain(mut self, storage: &EntityStorage) -> Result<QueryPlan, QueryError> {
+        self.explain = true;
+
+        let result = self.run(storage)?;
+        Ok(result.plan.expect("Explained query must include a plan"))
+    }
+
+    pub fn run(self, storage: &EntityStorage) -> Result<QueryResult, QueryError> {
+        let started = Instant::now();
+
+        // Read indices for the referenced fields in the query
+        let fields = read_fields(self.ref_fields.clone(), storage);
+        let indices = match &self.scope {
+            Some(scope) => storage.read_indices_in(scope, &fields),
+            None => storage.read_current_indices(&fields),
+        }?;
+
+        let read_elapsed = started.elapsed();
+        let indices = QueryIndices::new(indices);
+
+        // Apply filter given the indices
+        let (filter_result, filter_plan) = match self.filter.as_ref() {
+            Some(filter) if self.explain => {
+                let (result, plan) = indices.explain_filter(filter)?;
+                (result, Some(plan))
+            }
+            Some(filter) => (indices.execute_filter(filter)?, None),
+            None => (FilterResult::new(indices.indices.all.clone()), None),
+        };
+
+        let total = filter_result.hits.len();
+
+        // Suggest spelling corrections if searching for terms didn't match any item
+        let suggestions = match self.filter.as_ref() {
+            Some(filter) if total == 0 => indices.execute_corrections(filter),
+            _ => Vec::new(),
+        };
+
+        // Rank the filter results if the query searches for terms or sorts by relevance
+        let scored_filters = self
+            .filter
+            .as_ref()
+            .is_some_and(|filter| !filter.scored_filters().is_empty());
+        let ranked = scored_filters || self.sort.iter().any(|sort| sort.by == SCORE_FIELD);
+
+        let scores = match self.filter.as_ref() {
+            Some(filter) if ranked => {
+                indices.execute_scores(filter, &filter_result.hits, &self.boosts)
+            }
+            _ => HashMap::new(),
+        };
+
+        // Aggregate the filter results only from the indices, without reading any item
+        let groups = if self.group_by.is_some() || !self.aggregations.is_empty() {
+            indices.execute_aggregation(
+                &filter_result.hits,
+                self.group_by.as_ref(),
+                &self.aggregations,
+            )?
+        } else {
+            Vec::new()
         };
 
         // Sort filter results into a vector of IDs
-        let sorted_ids = self.sort(filter_result, &indices)?;
+        let sort_started = Instant::now();
+        let sorted_ids = self.sort(filter_result, &indices, &scores)?;
+        let sort_elapsed = sort_started.elapsed();
 
         // Apply pagination
         let paginated_ids = sorted_ids
@@ -278,22 +1372,114 @@ impl QueryExecution {
             .skip(self.pagination.start)
             .take(self.pagination.size);
 
-        // Read from the database the data of the paginated result
-        storage
-            .read_multiple(paginated_ids, &indices.indices)
-            .map_err(QueryError::Storage)
+        let items = self.read_items(paginated_ids, storage, &indices)?;
+
+        let start = self.pagination.start;
+        let size = items.len();
+
+        let scores = ranked.then(|| {
+            items
+                .iter()
+                .map(|item| {
+                    let position = id_to_position(item.id);
+                    scores.get(&position).copied().unwrap_or_default()
+                })
+                .collect()
+        });
+
+        let highlights = (!self.highlight.is_empty()).then(|| {
+            items
+                .iter()
+                .map(|item| match self.filter.as_ref() {
+                    Some(filter) => indices.execute_highlight(filter, &self.highlight, item),
+                    None => BTreeMap::new(),
+                })
+                .collect()
+        });
+
+        let plan = self.explain.then(|| QueryPlan {
+            entity: self.entity.clone(),
+            read_elapsed_micros: as_micros(read_elapsed),
+            deltas: self.scope.as_ref().map(|scope| {
+                let mut fields = indices.indices.affected.fields.clone();
+                fields.sort();
+
+                DeltaPlan {
+                    branch: scope.branch,
+                    date: scope.date.to_string(),
+                    fields,
+                    affected_items: indices.indices.affected.items.len(),
+                }
+            }),
+            filter: filter_plan,
+            sort: SortPlan {
+                strategy: if self.sort.is_empty() {
+                    SortStrategy::Id
+                } else {
+                    SortStrategy::Indices
+                },
+                keys: self.sort.iter().map(|sort| sort.to_string()).collect(),
+                elapsed_micros: as_micros(sort_elapsed),
+            },
+            total,
+            elapsed_micros: as_micros(started.elapsed()),
+        });
+
+        Ok(QueryResult {
+            items,
+            total,
+            start,
+            size,
+            has_more: ((start + size) as u64) < total,
+            groups,
+            plan,
+            scores,
+            suggestions,
+            highlights,
+        })
+    }
+
+    fn read_items<'a, T>(
+        &self,
+        ids: T,
+        storage: &EntityStorage,
+        indices: &QueryIndices,
+    ) -> Result<Vec<DataItem>, QueryError>
+    where
+        T: Iterator<Item = &'a DataItemId>,
+    {
+        let Some(projection) = self.projection.as_ref() else {
+            // Read from the database the data of the paginated result
+            return storage
+                .read_multiple(ids, &indices.indices)
+                .map_err(QueryError::Storage);
+        };
+
+        // Projected fields that are all indexed can be read directly from the indices,
+        // without the need of reading the items from the database.
+        if projection.iter().all(|field| indices.get(field).is_some()) {
+            return Ok(indices.read_projected(ids, projection));
+        }
+
+        let mut items = storage.read_multiple(ids, &indices.indices)?;
+        for item in &mut items {
+            item.fields.retain(|name, _| projection.contains(name));
+        }
+
+        Ok(items)
     }
 
     fn sort(
         &self,
         filter_result: FilterResult,
         indices: &QueryIndices,
+        scores: &HashMap<u32, f64>,
     ) -> Result<Vec<DataItemId>, QueryError> {
-        let sorted_ids = if let Some(sort) = &self.sort {
-            let sort_result = indices.execute_sort(&filter_result.hits, sort)?;
-            sort_result.into_iter().map(position_to_id).collect()
-        } else {
+        let sorted_ids = if self.sort.is_empty() {
             filter_result.hits.iter().map(position_to_id).collect()
+        } else {
+            let sort_result = indices.execute_sort(&filter_result.hits, &self.sort, scores)?;
+            sort_result.into_iter().map(position_to_id).collect()
         };
 
         Ok(sorted_ids)
@@ -302,10 +1488,15 @@ impl QueryExecution {
 
 /// A composite filter allows to combine multiple filter expressions using
 /// logical conjunction.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// Its JSON representation is defined by `CompositeFilterExternal`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(into = "CompositeFilterExternal", try_from = "CompositeFilterExternal")]
 pub enum CompositeFilter {
     And(Vec<CompositeFilter>),
     Or(Vec<CompositeFilter>),
+    /// Matches the items not matched by the filter. Items without a value for the field of a
+    /// negated single filter are not matched either, as in SQL.
     Not(Box<CompositeFilter>),
     Single(Filter),
 }
@@ -367,6 +1558,73 @@ impl CompositeFilter {
         })
     }
 
+    pub fn in_values(name: &str, values: Vec<FieldValue>) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::In(values),
+        })
+    }
+
+    pub fn starts_with(name: &str, value: FieldValue) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::StartsWith(value),
+        })
+    }
+
+    pub fn substring(name: &str, value: FieldValue) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::Substring(value),
+        })
+    }
+
+    pub fn fuzzy(name: &str, value: FieldValue, max_edits: u32) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::Fuzzy { value, max_edits },
+        })
+    }
+
+    pub fn near(name: &str, value: FieldValue, slop: u32, ordered: bool) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::Near {
+                value,
+                slop,
+                ordered,
+            },
+        })
+    }
+
+    pub fn like(name: &str, pattern: FieldValue) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::Like(pattern),
+        })
+    }
+
+    pub fn regex(name: &str, pattern: FieldValue) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::Regex(pattern),
+        })
+    }
+
+    pub fn is_null(name: &str) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::IsNull,
+        })
+    }
+
+    pub fn exists(name: &str) -> Self {
+        CompositeFilter::Single(Filter {
+            name: name.to_string(),
+            operation: FilterOperation::Exists,
+        })
+    }
+
     pub fn or(filters: Vec<CompositeFilter>) -> Self {
         CompositeFilter::Or(filters)
     }
@@ -389,6 +1647,58 @@ impl CompositeFilter {
             CompositeFilter::Single(filter) => vec![filter.name.to_string()],
         }
     }
+
+    /// Term filters contributing to the relevance score of the matching items. Filters
+    /// within a negation are excluded, since their terms are not present in the results.
+    fn scored_filters(&self) -> Vec<&Filter> {
+        match self {
+            CompositeFilter::And(composite) | CompositeFilter::Or(composite) => composite
+                .iter()
+                .flat_map(|filter| filter.scored_filters())
+                .collect(),
+            CompositeFilter::Not(_) => Vec::new(),
+            CompositeFilter::Single(filter) => match filter.operation {
+                FilterOperation::Contains(_)
+                | FilterOperation::Matches(_)
+                | FilterOperation::Near { .. } => vec![filter],
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl Display for CompositeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompositeFilter::And(filters) => {
+                for (index, filter) in filters.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " AND ")?;
+                    }
+                    // Disjunctions bind weaker than conjunctions and need to be grouped
+                    match filter {
+                        CompositeFilter::Or(_) => write!(f, "({})", filter)?,
+                        filter => write!(f, "{}", filter)?,
+                    }
+                }
+                Ok(())
+            }
+            CompositeFilter::Or(filters) => {
+                for (index, filter) in filters.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " OR ")?;
+                    }
+                    write!(f, "{}", filter)?;
+                }
+                Ok(())
+            }
+            CompositeFilter::Not(filter) => match filter.as_ref() {
+                CompositeFilter::And(_) | CompositeFilter::Or(_) => write!(f, "NOT ({})", filter),
+                filter => write!(f, "NOT {}", filter),
+            },
+            CompositeFilter::Single(filter) => write!(f, "{}", filter),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -423,7 +1733,8 @@ impl FilterResult {
 pub const DEFAULT_START_PAGE: usize = 0;
 pub const DEFAULT_PAGE_SIZE: usize = 500;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Pagination {
     start: usize,
     size: usize,
@@ -441,23 +1752,66 @@ impl Default for Pagination {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SortDirection {
+    #[default]
     ASC,
     DESC,
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Sort {
-    by: String,
-    direction: SortDirection,
+/// Placement of the items without a value for the sorted field.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NullsPlacement {
+    First,
+    #[default]
+    Last,
 }
 
-impl Sort {
-    pub fn new(by: &str) -> Self {
-        Sort {
-            by: by.to_string(),
+/// Pseudo-field to sort the items by their relevance score.
+pub const SCORE_FIELD: &str = "_score";
+
+/// Pseudo-field to search for terms in every field with a term index.
+pub const ALL_FIELDS: &str = "_all";
+
+/// Weight of the relevance score of a field when ranking the items.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Boost {
+    field: String,
+    weight: f64,
+}
+
+impl Boost {
+    pub fn new(field: &str, weight: f64) -> Self {
+        Boost {
+            field: field.to_string(),
+            weight,
+        }
+    }
+}
+
+impl Display for Boost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}^{}", self.field, self.weight)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sort {
+    by: String,
+    #[serde(default)]
+    direction: SortDirection,
+    #[serde(default)]
+    nulls: NullsPlacement,
+}
+
+impl Sort {
+    pub fn new(by: &str) -> Self {
+        Sort {
+            by: by.to_string(),
             direction: SortDirection::ASC,
+            nulls: NullsPlacement::default(),
         }
     }
 
@@ -466,11 +1820,131 @@ impl Sort {
         self
     }
 
+    pub fn with_nulls(mut self, nulls: NullsPlacement) -> Self {
+        self.nulls = nulls;
+        self
+    }
+
     fn get_referenced_fields(&self) -> Vec<String> {
+        if self.by == SCORE_FIELD {
+            return Vec::new();
+        }
+
         vec![self.by.to_string()]
     }
 }
 
+/// Split the items into groups of equal relevance score, ordered by score in the given
+/// direction. Items without a score are considered to have a score of zero.
+fn sort_by_score(
+    items: &RoaringBitmap,
+    scores: &HashMap<u32, f64>,
+    direction: &SortDirection,
+) -> Vec<RoaringBitmap> {
+    let mut groups = BTreeMap::<OrderedFloat<f64>, RoaringBitmap>::new();
+    for position in items {
+        let score = scores.get(&position).copied().unwrap_or_default();
+        groups
+            .entry(OrderedFloat(score))
+            .or_default()
+            .insert(position);
+    }
+
+    match direction {
+        SortDirection::ASC => groups.into_values().collect(),
+        SortDirection::DESC => groups.into_values().rev().collect(),
+    }
+}
+
+impl Display for Sort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:?}", self.by, self.direction)?;
+
+        if self.nulls == NullsPlacement::First {
+            write!(f, " NULLS FIRST")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AggregateFunction::Count => write!(f, "count"),
+            AggregateFunction::Sum => write!(f, "sum"),
+            AggregateFunction::Avg => write!(f, "avg"),
+            AggregateFunction::Min => write!(f, "min"),
+            AggregateFunction::Max => write!(f, "max"),
+        }
+    }
+}
+
+/// An aggregation applies a function over the values of a field, e.g. `avg(score)`.
+/// Without a field, the aggregation counts all the items, e.g. `count(*)`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Aggregation {
+    function: AggregateFunction,
+    #[serde(default)]
+    field: Option<String>,
+}
+
+impl Aggregation {
+    pub fn new(function: AggregateFunction, field: &str) -> Self {
+        Aggregation {
+            function,
+            field: Some(field.to_string()),
+        }
+    }
+
+    pub fn count_all() -> Self {
+        Aggregation {
+            function: AggregateFunction::Count,
+            field: None,
+        }
+    }
+
+    fn get_referenced_fields(&self) -> Vec<String> {
+        self.field.iter().cloned().collect()
+    }
+}
+
+impl Display for Aggregation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}({})", self.function, field),
+            None => write!(f, "{}(*)", self.function),
+        }
+    }
+}
+
+/// The aggregated values for the items sharing the same `key`. Items without a value
+/// for the grouped field, or all the items if no grouping is defined, have no key.
+#[derive(Debug, PartialEq)]
+pub struct AggregationGroup {
+    pub key: Option<FieldValue>,
+    pub count: u64,
+    pub values: Vec<AggregationValue>,
+}
+
+/// The value of an aggregation, named after it (e.g. `avg(score)`). The value is not
+/// defined if none of the items in the group has a value for the aggregated field.
+#[derive(Debug, PartialEq)]
+pub struct AggregationValue {
+    pub name: String,
+    pub value: Option<FieldValue>,
+}
+
 /// A single filter expression with a `name` identifying the field to match
 /// the filter against, and the filter operation.
 #[derive(Debug, PartialEq, Clone)]
@@ -479,6 +1953,57 @@ pub struct Filter {
     operation: FilterOperation,
 }
 
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (operator, value) = match &self.operation {
+            FilterOperation::Between(first, second) => {
+                write!(f, "{} BETWEEN ", self.name)?;
+                write_value(f, first)?;
+                write!(f, " AND ")?;
+                return write_value(f, second);
+            }
+            FilterOperation::Eq(value) => ("=", value),
+            FilterOperation::GreaterThan(value) => (">", value),
+            FilterOperation::GreaterOrEqual(value) => (">=", value),
+            FilterOperation::LessThan(value) => ("<", value),
+            FilterOperation::LessThanOrEqual(value) => ("<=", value),
+            FilterOperation::Contains(value) => ("CONTAINS", value),
+            FilterOperation::Matches(value) => ("MATCH", value),
+            FilterOperation::StartsWith(value) => ("STARTS WITH", value),
+            FilterOperation::Substring(value) => ("SUBSTRING", value),
+            FilterOperation::Like(value) => ("LIKE", value),
+            FilterOperation::Regex(value) => ("REGEX", value),
+            FilterOperation::In(values) => {
+                write!(f, "{} IN ", self.name)?;
+                return write_value(f, &FieldValue::Array(values.clone()));
+            }
+            FilterOperation::Fuzzy { value, max_edits } => {
+                write!(f, "{} FUZZY(", self.name)?;
+                write_value(f, value)?;
+                return write!(f, ", {})", max_edits);
+            }
+            FilterOperation::Near {
+                value,
+                slop,
+                ordered,
+            } => {
+                write!(f, "{} NEAR(", self.name)?;
+                write_value(f, value)?;
+                write!(f, ", {}", slop)?;
+                if *ordered {
+                    write!(f, ", ORDERED")?;
+                }
+                return write!(f, ")");
+            }
+            FilterOperation::IsNull => return write!(f, "{} IS NULL", self.name),
+            FilterOperation::Exists => return write!(f, "{} EXISTS", self.name),
+        };
+
+        write!(f, "{} {} ", self.name, operator)?;
+        write_value(f, value)
+    }
+}
+
 /// A filter operation collects all the available filter operations.
 #[derive(Debug, PartialEq, Clone)]
 pub enum FilterOperation {
@@ -490,6 +2015,234 @@ pub enum FilterOperation {
     LessThanOrEqual(FieldValue),
     Contains(FieldValue),
     Matches(FieldValue),
+    In(Vec<FieldValue>),
+    StartsWith(FieldValue),
+    /// Matches the items whose value contains the given value anywhere, answered by a
+    /// trigram index.
+    Substring(FieldValue),
+    /// Matches the items containing a term within `max_edits` edits of the value.
+    Fuzzy {
+        value: FieldValue,
+        max_edits: u32,
+    },
+    /// Matches the items containing all the terms of the value within a window of `slop`
+    /// extra positions, and in the same order if `ordered`.
+    Near {
+        value: FieldValue,
+        slop: u32,
+        ordered: bool,
+    },
+    /// Matches the items whose value (or any of its terms) matches a `LIKE` pattern.
+    Like(FieldValue),
+    /// Matches the items whose value (or any of its terms) matches a regular expression.
+    Regex(FieldValue),
+    /// Matches the items without a value for the field.
+    IsNull,
+    /// Matches the items with any value for the field.
+    Exists,
+}
+
+impl FilterOperation {
+    /// Whether the operation searches for words, being answered by a term index.
+    pub(crate) fn is_term(&self) -> bool {
+        matches!(
+            self,
+            FilterOperation::Contains(_)
+                | FilterOperation::Matches(_)
+                | FilterOperation::Fuzzy { .. }
+                | FilterOperation::Near { .. }
+        )
+    }
+}
+
+/// The JSON representation of a composite filter. Its values use the same representation
+/// as the data items (e.g. `5` or `"Football"`), so that integers and decimals are kept
+/// apart when reading the query back.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum CompositeFilterExternal {
+    And(Vec<CompositeFilterExternal>),
+    Or(Vec<CompositeFilterExternal>),
+    Not(Box<CompositeFilterExternal>),
+    Single(FilterExternal),
+}
+
+#[derive(Serialize, Deserialize)]
+struct FilterExternal {
+    name: String,
+    operation: FilterOperationExternal,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+enum FilterOperationExternal {
+    Eq(FieldValueExternal),
+    Between(FieldValueExternal, FieldValueExternal),
+    GreaterThan(FieldValueExternal),
+    GreaterOrEqual(FieldValueExternal),
+    LessThan(FieldValueExternal),
+    LessThanOrEqual(FieldValueExternal),
+    Contains(FieldValueExternal),
+    Matches(FieldValueExternal),
+    In(Vec<FieldValueExternal>),
+    StartsWith(FieldValueExternal),
+    Substring(FieldValueExternal),
+    Fuzzy {
+        value: FieldValueExternal,
+        max_edits: u32,
+    },
+    Near {
+        value: FieldValueExternal,
+        slop: u32,
+        ordered: bool,
+    },
+    Like(FieldValueExternal),
+    Regex(FieldValueExternal),
+    IsNull,
+    Exists,
+}
+
+impl From<CompositeFilter> for CompositeFilterExternal {
+    fn from(filter: CompositeFilter) -> Self {
+        let as_external =
+            |filters: Vec<CompositeFilter>| filters.into_iter().map(Self::from).collect();
+
+        match filter {
+            CompositeFilter::And(filters) => CompositeFilterExternal::And(as_external(filters)),
+            CompositeFilter::Or(filters) => CompositeFilterExternal::Or(as_external(filters)),
+            CompositeFilter::Not(filter) => {
+                CompositeFilterExternal::Not(Box::new((*filter).into()))
+            }
+            CompositeFilter::Single(filter) => CompositeFilterExternal::Single(FilterExternal {
+                name: filter.name,
+                operation: filter.operation.into(),
+            }),
+        }
+    }
+}
+
+impl From<FilterOperation> for FilterOperationExternal {
+    fn from(operation: FilterOperation) -> Self {
+        let value = |value: FieldValue| FieldValueExternal::from(&value);
+
+        match operation {
+            FilterOperation::Eq(v) => FilterOperationExternal::Eq(value(v)),
+            FilterOperation::Between(first, second) => {
+                FilterOperationExternal::Between(value(first), value(second))
+            }
+            FilterOperation::GreaterThan(v) => FilterOperationExternal::GreaterThan(value(v)),
+            FilterOperation::GreaterOrEqual(v) => FilterOperationExternal::GreaterOrEqual(value(v)),
+            FilterOperation::LessThan(v) => FilterOperationExternal::LessThan(value(v)),
+            FilterOperation::LessThanOrEqual(v) => {
+                FilterOperationExternal::LessThanOrEqual(value(v))
+            }
+            FilterOperation::Contains(v) => FilterOperationExternal::Contains(value(v)),
+            FilterOperation::Matches(v) => FilterOperationExternal::Matches(value(v)),
+            FilterOperation::In(values) => {
+                FilterOperationExternal::In(values.into_iter().map(value).collect())
+            }
+            FilterOperation::StartsWith(v) => FilterOperationExternal::StartsWith(value(v)),
+            FilterOperation::Substring(v) => FilterOperationExternal::Substring(value(v)),
+            FilterOperation::Fuzzy {
+                value: v,
+                max_edits,
+            } => FilterOperationExternal::Fuzzy {
+                value: value(v),
+                max_edits,
+            },
+            FilterOperation::Near {
+                value: v,
+                slop,
+                ordered,
+            } => FilterOperationExternal::Near {
+                value: value(v),
+                slop,
+                ordered,
+            },
+            FilterOperation::Like(v) => FilterOperationExternal::Like(value(v)),
+            FilterOperation::Regex(v) => FilterOperationExternal::Regex(value(v)),
+            FilterOperation::IsNull => FilterOperationExternal::IsNull,
+            FilterOperation::Exists => FilterOperationExternal::Exists,
+        }
+    }
+}
+
+impl TryFrom<CompositeFilterExternal> for CompositeFilter {
+    type Error = &'static str;
+
+    fn try_from(filter: CompositeFilterExternal) -> Result<Self, Self::Error> {
+        let from_external = |filters: Vec<CompositeFilterExternal>| {
+            filters
+                .into_iter()
+                .map(Self::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(match filter {
+            CompositeFilterExternal::And(filters) => CompositeFilter::And(from_external(filters)?),
+            CompositeFilterExternal::Or(filters) => CompositeFilter::Or(from_external(filters)?),
+            CompositeFilterExternal::Not(filter) => {
+                CompositeFilter::Not(Box::new((*filter).try_into()?))
+            }
+            CompositeFilterExternal::Single(filter) => CompositeFilter::Single(Filter {
+                name: filter.name,
+                operation: filter.operation.try_into()?,
+            }),
+        })
+    }
+}
+
+impl TryFrom<FilterOperationExternal> for FilterOperation {
+    type Error = &'static str;
+
+    fn try_from(operation: FilterOperationExternal) -> Result<Self, Self::Error> {
+        let value = FieldValue::try_from;
+
+        Ok(match operation {
+            FilterOperationExternal::Eq(v) => FilterOperation::Eq(value(v)?),
+            FilterOperationExternal::Between(first, second) => {
+                FilterOperation::Between(value(first)?, value(second)?)
+            }
+            FilterOperationExternal::GreaterThan(v) => FilterOperation::GreaterThan(value(v)?),
+            FilterOperationExternal::GreaterOrEqual(v) => {
+                FilterOperation::GreaterOrEqual(value(v)?)
+            }
+            FilterOperationExternal::LessThan(v) => FilterOperation::LessThan(value(v)?),
+            FilterOperationExternal::LessThanOrEqual(v) => {
+                FilterOperation::LessThanOrEqual(value(v)?)
+            }
+            FilterOperationExternal::Contains(v) => FilterOperation::Contains(value(v)?),
+            FilterOperationExternal::Matches(v) => FilterOperation::Matches(value(v)?),
+            FilterOperationExternal::In(values) => FilterOperation::In(
+                values
+                    .into_iter()
+                    .map(value)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            FilterOperationExternal::StartsWith(v) => FilterOperation::StartsWith(value(v)?),
+            FilterOperationExternal::Substring(v) => FilterOperation::Substring(value(v)?),
+            FilterOperationExternal::Fuzzy {
+                value: v,
+                max_edits,
+            } => FilterOperation::Fuzzy {
+                value: value(v)?,
+                max_edits,
+            },
+            FilterOperationExternal::Near {
+                value: v,
+                slop,
+                ordered,
+            } => FilterOperation::Near {
+                value: value(v)?,
+                slop,
+                ordered,
+            },
+            FilterOperationExternal::Like(v) => FilterOperation::Like(value(v)?),
+            FilterOperationExternal::Regex(v) => FilterOperation::Regex(value(v)?),
+            FilterOperationExternal::IsNull => FilterOperation::IsNull,
+            FilterOperationExternal::Exists => FilterOperation::Exists,
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -501,6 +2254,13 @@ pub enum FilterName {
     LessThan,
     LessThanOrEqual,
     Contains,
+    In,
+    StartsWith,
+    Substring,
+    Fuzzy,
+    Near,
+    Like,
+    Regex,
 }
 
 impl Display for FilterName {
@@ -513,6 +2273,13 @@ impl Display for FilterName {
             FilterName::LessThan => write!(f, "less than"),
             FilterName::LessThanOrEqual => write!(f, "less than or equal"),
             FilterName::Contains => write!(f, "contains"),
+            FilterName::In => write!(f, "in"),
+            FilterName::StartsWith => write!(f, "starts with"),
+            FilterName::Substring => write!(f, "substring"),
+            FilterName::Fuzzy => write!(f, "fuzzy"),
+            FilterName::Near => write!(f, "near"),
+            FilterName::Like => write!(f, "like"),
+            FilterName::Regex => write!(f, "regex"),
         }
     }
 }
@@ -534,19 +2301,141 @@ impl DeltaChange {
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct ParsedQuery {
+/// The definition of a query, either parsed from the text query language or
+/// deserialized from its JSON representation. Its `Display` implementation prints
+/// the query back using the text query language.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Query {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    explain: bool,
     entity: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    projection: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     scope: Option<DeltaScope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     filter: Option<CompositeFilter>,
-    sort: Option<Sort>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    boosts: Vec<Boost>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    highlight: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aggregations: Vec<Aggregation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sort: Vec<Sort>,
+    #[serde(default)]
     pagination: Pagination,
 }
 
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        QueryParser::parse_query(input)
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.explain {
+            write!(f, "EXPLAIN ")?;
+        }
+
+        if let Some(projection) = &self.projection {
+            write!(f, "SELECT {} ", projection.join(", "))?;
+        }
+
+        write!(f, "FROM {}", self.entity)?;
+
+        if let Some(filter) = &self.filter {
+            write!(f, " WHERE {}", filter)?;
+        }
+
+        if !self.boosts.is_empty() {
+            write!(f, " BOOST {}", join_display(&self.boosts))?;
+        }
+
+        if !self.highlight.is_empty() {
+            write!(f, " HIGHLIGHT {}", self.highlight.join(", "))?;
+        }
+
+        if let Some(group_by) = &self.group_by {
+            write!(f, " GROUP BY {}", group_by)?;
+        }
+
+        if !self.aggregations.is_empty() {
+            write!(f, " AGG {}", join_display(&self.aggregations))?;
+        }
+
+        if let Some(scope) = &self.scope {
+            if let Some(branch) = scope.branch {
+                write!(f, " BRANCH {}", branch)?;
+            }
+            write!(f, " AS OF \"{}\"", scope.date)?;
+        }
+
+        if !self.sort.is_empty() {
+            write!(f, " ORDER BY {}", join_display(&self.sort))?;
+        }
+
+        if self.pagination.start != DEFAULT_START_PAGE {
+            write!(f, " OFFSET {}", self.pagination.start)?;
+        }
+
+        if self.pagination.size != DEFAULT_PAGE_SIZE {
+            write!(f, " LIMIT {}", self.pagination.size)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn join_display<T: Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Print a value using the syntax of the text query language.
+fn write_value(f: &mut std::fmt::Formatter<'_>, value: &FieldValue) -> std::fmt::Result {
+    match value {
+        FieldValue::Bool(value) => write!(f, "{}", value),
+        FieldValue::Integer(value) => write!(f, "{}", value),
+        // Printed with a fraction, so that they are not parsed back as integers
+        FieldValue::Decimal(value) => write!(f, "{:?}", value.into_inner()),
+        FieldValue::String(value) => {
+            write!(f, "\"")?;
+            for c in value.chars() {
+                match c {
+                    '"' => write!(f, "\\\"")?,
+                    '\\' => write!(f, "\\\\")?,
+                    c => write!(f, "{}", c)?,
+                }
+            }
+            write!(f, "\"")
+        }
+        FieldValue::Array(values) => {
+            write!(f, "[")?;
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write_value(f, value)?;
+            }
+            write!(f, "]")
+        }
+    }
+}
+
 // TODO: implement parsing for "contains"
 #[derive(pest_derive::Parser)]
 #[grammar_inline = r#"
-    WHITESPACE = _{ " " }
+    WHITESPACE = _{ " " | "\t" | "\r" | "\n" }
     NAME_CHAR  = _{ ASCII_ALPHA | ASCII_DIGIT | "." | "_" }
     name       = @{ NAME_CHAR+ }
     number     = @{ "-"? ~ ("0" | ASCII_NONZERO_DIGIT ~ ASCII_DIGIT*) ~ ("." ~ ASCII_DIGIT*)? ~ (^"e" ~ ("+" | "-")? ~ ASCII_DIGIT+)? }
@@ -569,6 +2458,12 @@ pub(crate) struct ParsedQuery {
     lt_operator         = { "<" }
     contains_operator   = { ^"CONTAINS" }
     match_operator      = { ^"MATCH" }
+    in_operator         = @{ ^"IN" ~ !NAME_CHAR }
+    not_in_operator     = @{ ^"NOT" ~ WHITESPACE+ ~ ^"IN" ~ !NAME_CHAR }
+    starts_with_operator = @{ ^"STARTS" ~ WHITESPACE+ ~ ^"WITH" ~ !NAME_CHAR }
+    substring_operator   = @{ ^"SUBSTRING" ~ !NAME_CHAR }
+    like_operator        = @{ ^"LIKE" ~ !NAME_CHAR }
+    regex_operator       = @{ ^"REGEX" ~ !NAME_CHAR }
     comparison_operator = {
         eq_operator
         | not_eq_operator
@@ -578,44 +2473,114 @@ pub(crate) struct ParsedQuery {
         | lt_operator
         | contains_operator
         | match_operator
+        | in_operator
+        | not_in_operator
+        | starts_with_operator
+        | substring_operator
+        | like_operator
+        | regex_operator
     }
-    logical_operator    = { ^"AND" | ^"OR" }
-
-    ASC  = { ^"ASC" }
-    DESC = { ^"DESC" }
-
+    and_operator        = @{ ^"AND" ~ !NAME_CHAR }
+    or_operator         = @{ ^"OR" ~ !NAME_CHAR }
+    logical_operator    = _{ and_operator | or_operator }
+    not_operator        = @{ ^"NOT" ~ !NAME_CHAR }
+    between_operator    = @{ ^"BETWEEN" ~ !NAME_CHAR }
+    is_null_operator     = @{ ^"IS" ~ WHITESPACE+ ~ ^"NULL" ~ !NAME_CHAR }
+    is_not_null_operator = @{ ^"IS" ~ WHITESPACE+ ~ ^"NOT" ~ WHITESPACE+ ~ ^"NULL" ~ !NAME_CHAR }
+    exists_operator      = @{ ^"EXISTS" ~ !NAME_CHAR }
+    null_operator        = { is_null_operator | is_not_null_operator | exists_operator }
+    fuzzy_operator       = @{ ^"FUZZY" ~ !NAME_CHAR }
+    near_operator        = @{ ^"NEAR" ~ !NAME_CHAR }
+    ORDERED              = @{ ^"ORDERED" ~ !NAME_CHAR }
+
+    ASC         = { ^"ASC" }
+    DESC        = { ^"DESC" }
+    NULLS_FIRST = { ^"NULLS" ~ ^"FIRST" }
+    NULLS_LAST  = { ^"NULLS" ~ ^"LAST" }
+    sort_key    = { name ~ (ASC | DESC)? ~ (NULLS_FIRST | NULLS_LAST)? }
+
+    count_function     = { ^"COUNT" }
+    sum_function       = { ^"SUM" }
+    avg_function       = { ^"AVG" }
+    min_function       = { ^"MIN" }
+    max_function       = { ^"MAX" }
+    aggregate_function = { count_function | sum_function | avg_function | min_function | max_function }
+    all_fields         = { "*" }
+    aggregation        = { aggregate_function ~ "(" ~ (name | all_fields) ~ ")" }
+
+    boost = { name ~ "^" ~ number }
+
+    EXPLAIN  = { ^"EXPLAIN" }
+    SELECT   = { ^"SELECT" ~ name ~ ("," ~ name)* }
     FROM     = { ^"FROM" ~ name }
     WHERE    = { ^"WHERE" ~ composite }
-    ORDER_BY = { ^"ORDER BY" ~ name ~ (ASC | DESC)? }
+    BOOST    = { ^"BOOST" ~ boost ~ ("," ~ boost)* }
+    HIGHLIGHT = { ^"HIGHLIGHT" ~ name ~ ("," ~ name)* }
+    GROUP_BY = { ^"GROUP" ~ ^"BY" ~ name }
+    AGG      = { ^"AGG" ~ aggregation ~ ("," ~ aggregation)* }
+    ORDER_BY = { ^"ORDER" ~ ^"BY" ~ sort_key ~ ("," ~ sort_key)* }
     LIMIT    = { ^"LIMIT" ~ number }
     OFFSET   = { ^"OFFSET" ~ number }
-    AS_OF    = { ^"AS OF" ~ date }
+    AS_OF    = { ^"AS" ~ ^"OF" ~ date }
     BRANCH    = { ^"BRANCH" ~ number }
 
-    statement = { "("{0, 1} ~ name ~ comparison_operator ~ value ~ ")"{0, 1} }
-    composite = { "("{0, 1} ~ statement ~ (logical_operator ~ composite)* ~ ")"{0, 1} }
+    statement         = { name ~ comparison_operator ~ value }
+    between_statement = { name ~ between_operator ~ value ~ and_operator ~ value }
+    null_statement    = { name ~ null_operator }
+    fuzzy_statement   = { name ~ fuzzy_operator ~ ("(" ~ value ~ "," ~ number ~ ")" | value) }
+    near_statement    = { name ~ near_operator ~ "(" ~ value ~ "," ~ number ~ ("," ~ ORDERED)? ~ ")" }
+    group             = { "(" ~ composite ~ ")" }
+    primary           = _{ between_statement | null_statement | fuzzy_statement | near_statement | statement | group }
+    term      = _{ not_operator* ~ primary }
+    composite = { term ~ (logical_operator ~ term)* }
 
     // Allow any order of OFFSET and LIMIT
-    query     = { FROM ~ WHERE? ~ BRANCH? ~ AS_OF? ~ ORDER_BY? ~ OFFSET? ~ LIMIT? ~ OFFSET?  }
+    query     = _{ EXPLAIN? ~ SELECT? ~ FROM ~ WHERE? ~ BOOST? ~ HIGHLIGHT? ~ GROUP_BY? ~ AGG? ~ BRANCH? ~ AS_OF? ~ ORDER_BY? ~ OFFSET? ~ LIMIT? ~ OFFSET? ~ EOI }
 "#]
 pub(crate) struct QueryParser;
 
+/// Maximum amount of edits allowed by `FUZZY` statements not defining it explicitly.
+const DEFAULT_FUZZY_EDITS: u32 = 2;
+
+/// Precedence of the logical operators when parsing composite filters, defined from
+/// lowest to highest. `NOT` binds tighter than `AND`, which binds tighter than `OR`.
+/// Both `AND` and `OR` are left-associative.
+static PRATT_PARSER: LazyLock<PrattParser<Rule>> = LazyLock::new(|| {
+    PrattParser::new()
+        .op(Op::infix(Rule::or_operator, Assoc::Left))
+        .op(Op::infix(Rule::and_operator, Assoc::Left))
+        .op(Op::prefix(Rule::not_operator))
+});
+
 impl QueryParser {
-    pub(crate) fn parse_query(input: &str) -> Result<ParsedQuery, ParseError> {
-        let input = Self::normalize(input);
-        let mut pairs = Self::parse(Rule::query, &input)
-            .map_err(|err| ParseError::QueryParse(err.line().to_string()))?;
+    pub(crate) fn parse_query(input: &str) -> Result<Query, ParseError> {
+        if input.trim().is_empty() {
+            return Err(ParseError::EmptyQuery);
+        }
+
+        let mut pairs = Self::parse(Rule::query, input)
+            .map_err(ParseError::from_pest)?
+            .peekable();
 
-        let query_pair = pairs.next().ok_or(ParseError::EmptyQuery)?;
-        let mut pairs = query_pair.into_inner();
+        let explain = pairs
+            .next_if(|pair| pair.as_rule() == Rule::EXPLAIN)
+            .is_some();
 
-        let from_pair = pairs.next().ok_or(ParseError::InvalidQuery(
+        let projection = pairs
+            .next_if(|pair| pair.as_rule() == Rule::SELECT)
+            .map(Self::parse_select);
+
+        let from_pair = pairs.next().ok_or(ParseError::invalid(
             "query must start with a FROM statement",
         ))?;
 
         let entity = Self::parse_from(from_pair)?;
         let mut filter = None;
-        let mut sort = None;
+        let mut boosts = Vec::new();
+        let mut highlight = Vec::new();
+        let mut group_by = None;
+        let mut aggregations = Vec::new();
+        let mut sort = Vec::new();
         let mut start = None;
         let mut size = None;
         let mut delta_scope_date = None;
@@ -626,6 +2591,18 @@ impl QueryParser {
                 Rule::WHERE => {
                     filter = Self::parse_where(pair)?;
                 }
+                Rule::BOOST => {
+                    boosts = Self::parse_boosts(pair)?;
+                }
+                Rule::HIGHLIGHT => {
+                    highlight = Self::parse_highlight(pair);
+                }
+                Rule::GROUP_BY => {
+                    group_by = Self::parse_group_by(pair)?;
+                }
+                Rule::AGG => {
+                    aggregations = Self::parse_aggregations(pair)?;
+                }
                 Rule::ORDER_BY => {
                     sort = Self::parse_sort(pair)?;
                 }
@@ -633,7 +2610,10 @@ impl QueryParser {
                     let mut inner = pair.into_inner();
                     size = if let Some(limit) = inner.next() {
                         Some(limit.as_str().parse::<usize>().map_err(|_| {
-                            ParseError::InvalidQuery("expected numeric value after LIMIT statement")
+                            ParseError::invalid_at(
+                                "expected numeric value after LIMIT statement",
+                                &limit,
+                            )
                         })?)
                     } else {
                         None
@@ -643,8 +2623,9 @@ impl QueryParser {
                     let mut inner = pair.into_inner();
                     start = if let Some(offset) = inner.next() {
                         Some(offset.as_str().parse::<usize>().map_err(|_| {
-                            ParseError::InvalidQuery(
+                            ParseError::invalid_at(
                                 "expected numeric value after OFFSET statement",
+                                &offset,
                             )
                         })?)
                     } else {
@@ -653,8 +2634,8 @@ impl QueryParser {
                 }
                 Rule::AS_OF => {
                     let mut inner = pair.into_inner();
-                    delta_scope_date = if let Some(date) = inner.next() {
-                        let Ok(date) = date
+                    delta_scope_date = if let Some(date_pair) = inner.next() {
+                        let Ok(date) = date_pair
                             .as_str()
                             // Remove double quotes from beginning and end (as stated in the grammar)
                             .trim_start_matches('"')
@@ -662,8 +2643,9 @@ impl QueryParser {
                             .parse::<String>();
 
                         Some(parse_date(&date).map_err(|_| {
-                            ParseError::InvalidQuery(
+                            ParseError::invalid_at(
                                 "date value has the wrong formatting for AS OF statement",
+                                &date_pair,
                             )
                         })?)
                     } else {
@@ -674,8 +2656,9 @@ impl QueryParser {
                     let mut inner = pair.into_inner();
                     delta_scope_branch = if let Some(branch) = inner.next() {
                         Some(branch.as_str().parse::<u32>().map_err(|_| {
-                            ParseError::InvalidQuery(
+                            ParseError::invalid_at(
                                 "expected numeric value after BRANCH statement",
+                                &branch,
                             )
                         })?)
                     } else {
@@ -696,22 +2679,68 @@ impl QueryParser {
             branch: delta_scope_branch,
         });
 
-        Ok(ParsedQuery {
+        Ok(Query {
+            explain,
             entity,
+            projection,
             filter,
+            boosts,
+            highlight,
+            group_by,
+            aggregations,
             scope,
             sort,
             pagination,
         })
     }
 
+    fn parse_boosts(pair: Pair<Rule>) -> Result<Vec<Boost>, ParseError> {
+        let mut boosts = Vec::new();
+
+        for boost in pair.into_inner() {
+            let mut inner = boost.into_inner();
+
+            let field = inner
+                .next()
+                .ok_or(ParseError::invalid("expected field in BOOST statement"))?;
+            let weight = inner
+                .next()
+                .ok_or(ParseError::invalid("expected weight in BOOST statement"))?;
+
+            let value = weight
+                .as_str()
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(|| {
+                    ParseError::invalid_at("expected a positive weight in BOOST statement", &weight)
+                })?;
+
+            boosts.push(Boost::new(field.as_str(), value));
+        }
+
+        Ok(boosts)
+    }
+
+    fn parse_highlight(pair: Pair<Rule>) -> Vec<String> {
+        pair.into_inner()
+            .map(|field| field.as_str().to_string())
+            .collect()
+    }
+
+    fn parse_select(pair: Pair<Rule>) -> Vec<String> {
+        pair.into_inner()
+            .map(|field| field.as_str().to_string())
+            .collect()
+    }
+
     fn parse_from(pair: Pair<Rule>) -> Result<String, ParseError> {
         if let Rule::FROM = pair.as_rule() {
             let mut inner = pair.into_inner();
 
             let entity = inner
                 .next()
-                .ok_or(ParseError::InvalidQuery(
+                .ok_or(ParseError::invalid(
                     "expected entity name in FROM statement",
                 ))?
                 .as_str()
@@ -720,7 +2749,7 @@ impl QueryParser {
             return Ok(entity);
         }
 
-        Err(ParseError::InvalidQuery(
+        Err(ParseError::invalid(
             "query must start with a FROM statement",
         ))
     }
@@ -729,9 +2758,9 @@ impl QueryParser {
         if let Rule::WHERE = pair.as_rule() {
             let mut inner = pair.into_inner();
 
-            let filter_statement = inner.next().ok_or(ParseError::InvalidQuery(
-                "expected filter after WHERE statement",
-            ))?;
+            let filter_statement = inner
+                .next()
+                .ok_or(ParseError::invalid("expected filter after WHERE statement"))?;
 
             let filter = Self::parse_filter_statement(filter_statement)?;
             return Ok(Some(filter));
@@ -740,145 +2769,371 @@ impl QueryParser {
         Ok(None)
     }
 
-    fn parse_sort(pair: Pair<Rule>) -> Result<Option<Sort>, ParseError> {
-        if let Rule::ORDER_BY = pair.as_rule() {
-            let mut inner = pair.into_inner();
+    fn parse_group_by(pair: Pair<Rule>) -> Result<Option<String>, ParseError> {
+        let field = pair
+            .into_inner()
+            .next()
+            .ok_or(ParseError::invalid("expected field in GROUP BY statement"))?;
 
-            let by = inner
-                .next()
-                .ok_or(ParseError::InvalidQuery(
-                    "expected field in ORDER BY statement",
-                ))?
-                .as_str();
+        Ok(Some(field.as_str().to_string()))
+    }
 
-            let mut sort = Sort::new(by);
+    fn parse_aggregations(pair: Pair<Rule>) -> Result<Vec<Aggregation>, ParseError> {
+        pair.into_inner().map(Self::parse_aggregation).collect()
+    }
 
-            if let Some(direction) = inner.next() {
-                sort = match direction.as_rule() {
-                    Rule::ASC => sort.with_direction(SortDirection::ASC),
-                    Rule::DESC => sort.with_direction(SortDirection::DESC),
-                    _ => unreachable!(),
-                };
-            }
+    fn parse_aggregation(pair: Pair<Rule>) -> Result<Aggregation, ParseError> {
+        let mut inner = pair.into_inner();
+
+        let function = inner
+            .next()
+            .and_then(|function| function.into_inner().next())
+            .ok_or(ParseError::invalid("expected function in AGG statement"))?;
+
+        let function = match function.as_rule() {
+            Rule::count_function => AggregateFunction::Count,
+            Rule::sum_function => AggregateFunction::Sum,
+            Rule::avg_function => AggregateFunction::Avg,
+            Rule::min_function => AggregateFunction::Min,
+            Rule::max_function => AggregateFunction::Max,
+            _ => return Err(ParseError::UnknownOperator),
+        };
 
-            return Ok(Some(sort));
-        }
+        let field = inner.next().ok_or(ParseError::invalid(
+            "expected field in aggregation function",
+        ))?;
 
-        Ok(None)
+        match (field.as_rule(), function) {
+            (Rule::all_fields, AggregateFunction::Count) => Ok(Aggregation::count_all()),
+            (Rule::all_fields, _) => Err(ParseError::invalid_at(
+                "only count can be applied to all the items in AGG statement",
+                &field,
+            )),
+            _ => Ok(Aggregation::new(function, field.as_str())),
+        }
     }
 
-    fn parse_filter_statement(pair: Pair<Rule>) -> Result<CompositeFilter, ParseError> {
-        match pair.as_rule() {
-            Rule::WHITESPACE
-            | Rule::NAME_CHAR
-            | Rule::name
-            | Rule::number
-            | Rule::char
-            | Rule::string
-            | Rule::boolean
-            | Rule::date
-            | Rule::array
-            | Rule::value
-            | Rule::comparison_operator
-            | Rule::eq_operator
-            | Rule::not_eq_operator
-            | Rule::ge_operator
-            | Rule::le_operator
-            | Rule::gt_operator
-            | Rule::lt_operator
-            | Rule::contains_operator
-            | Rule::match_operator
-            | Rule::logical_operator
-            | Rule::FROM
-            | Rule::WHERE
-            | Rule::ORDER_BY
-            | Rule::LIMIT
-            | Rule::OFFSET
-            | Rule::AS_OF
-            | Rule::BRANCH
-            | Rule::ASC
-            | Rule::DESC
-            | Rule::query => unreachable!(),
-            Rule::statement => {
-                let mut inner = pair.into_inner();
-
-                let name = inner
-                    .next()
-                    .ok_or(ParseError::InvalidQuery(
-                        "expected property name in filter statement",
-                    ))?
-                    .as_str();
+    fn parse_sort(pair: Pair<Rule>) -> Result<Vec<Sort>, ParseError> {
+        let mut sorts = Vec::new();
 
-                let operator = inner.next().ok_or(ParseError::InvalidQuery(
-                    "expected comparison operator in filter statement",
-                ))?;
-
-                let value = inner.next().ok_or(ParseError::InvalidQuery(
-                    "expected value in filter statement",
-                ))?;
-
-                let value = Self::parse_value(value);
-
-                if let Rule::comparison_operator = operator.as_rule() {
-                    let operator = operator
-                        .into_inner()
-                        .next()
-                        .ok_or(ParseError::InvalidQuery(
-                            "expected comparison operator in filter statement",
-                        ))?
-                        .as_rule();
-
-                    match operator {
-                        Rule::eq_operator => Ok(CompositeFilter::eq(name, value)),
-                        Rule::not_eq_operator => {
-                            Ok(CompositeFilter::negate(CompositeFilter::eq(name, value)))
-                        }
-                        Rule::ge_operator => Ok(CompositeFilter::ge(name, value)),
-                        Rule::le_operator => Ok(CompositeFilter::le(name, value)),
-                        Rule::gt_operator => Ok(CompositeFilter::gt(name, value)),
-                        Rule::lt_operator => Ok(CompositeFilter::lt(name, value)),
-                        Rule::contains_operator => Ok(CompositeFilter::contains(name, value)),
-                        Rule::match_operator => Ok(CompositeFilter::matches(name, value)),
-                        _ => Err(ParseError::UnknownOperator),
-                    }
-                } else {
-                    Err(ParseError::InvalidQuery(
-                        "expected operator to be a comparison operator in filter statement",
-                    ))
-                }
+        if let Rule::ORDER_BY = pair.as_rule() {
+            for sort_key in pair.into_inner() {
+                sorts.push(Self::parse_sort_key(sort_key)?);
             }
-            Rule::composite => {
-                let mut inner = pair.into_inner();
+        }
 
-                let left = inner.next().ok_or(ParseError::InvalidQuery(
-                    "expected left statement in composite rule",
-                ))?;
+        Ok(sorts)
+    }
 
-                let Some(operator) = inner.next() else {
-                    return Self::parse_filter_statement(left);
-                };
+    fn parse_sort_key(pair: Pair<Rule>) -> Result<Sort, ParseError> {
+        let mut inner = pair.into_inner();
 
-                let right = inner.next().ok_or(ParseError::InvalidQuery(
-                    "expected right statement in composite rule",
-                ))?;
+        let by = inner
+            .next()
+            .ok_or(ParseError::invalid("expected field in ORDER BY statement"))?
+            .as_str();
 
-                let left = Self::parse_filter_statement(left)?;
-                let right = Self::parse_filter_statement(right)?;
+        let mut sort = Sort::new(by);
 
-                let operator = operator.as_str();
-                match operator {
-                    "AND" => Ok(CompositeFilter::And(vec![left, right])),
-                    "OR" => Ok(CompositeFilter::Or(vec![left, right])),
-                    _ => Err(ParseError::UnknownOperator),
-                }
-            }
+        for modifier in inner {
+            sort = match modifier.as_rule() {
+                Rule::ASC => sort.with_direction(SortDirection::ASC),
+                Rule::DESC => sort.with_direction(SortDirection::DESC),
+                Rule::NULLS_FIRST => sort.with_nulls(NullsPlacement::First),
+                Rule::NULLS_LAST => sort.with_nulls(NullsPlacement::Last),
+                _ => unreachable!(),
+            };
         }
+
+        Ok(sort)
     }
 
-    fn parse_value(pair: Pair<Rule>) -> FieldValue {
+    fn parse_filter_statement(pair: Pair<Rule>) -> Result<CompositeFilter, ParseError> {
         match pair.as_rule() {
-            Rule::WHITESPACE
-            | Rule::NAME_CHAR
+            Rule::statement => Self::parse_statement(pair),
+            Rule::between_statement => Self::parse_between_statement(pair),
+            Rule::null_statement => Self::parse_null_statement(pair),
+            Rule::fuzzy_statement => Self::parse_fuzzy_statement(pair),
+            Rule::near_statement => Self::parse_near_statement(pair),
+            Rule::group => {
+                let composite = pair
+                    .into_inner()
+                    .next()
+                    .ok_or(ParseError::invalid("expected filter inside parentheses"))?;
+
+                Self::parse_filter_statement(composite)
+            }
+            Rule::composite => PRATT_PARSER
+                .map_primary(Self::parse_filter_statement)
+                .map_prefix(|operator, filter| match operator.as_rule() {
+                    Rule::not_operator => Ok(CompositeFilter::negate(filter?)),
+                    _ => Err(ParseError::UnknownOperator),
+                })
+                .map_infix(|left, operator, right| match operator.as_rule() {
+                    Rule::and_operator => Ok(Self::join_and(left?, right?)),
+                    Rule::or_operator => Ok(Self::join_or(left?, right?)),
+                    _ => Err(ParseError::UnknownOperator),
+                })
+                .parse(pair.into_inner()),
+            _ => Err(ParseError::invalid("expected statement or group in filter")),
+        }
+    }
+
+    fn parse_statement(pair: Pair<Rule>) -> Result<CompositeFilter, ParseError> {
+        let mut inner = pair.into_inner();
+
+        let name = inner
+            .next()
+            .ok_or(ParseError::invalid(
+                "expected property name in filter statement",
+            ))?
+            .as_str();
+
+        let operator = inner.next().ok_or(ParseError::invalid(
+            "expected comparison operator in filter statement",
+        ))?;
+
+        let value_pair = inner
+            .next()
+            .ok_or(ParseError::invalid("expected value in filter statement"))?;
+
+        let value = Self::parse_value(value_pair.clone());
+
+        if let Rule::comparison_operator = operator.as_rule() {
+            let operator = operator
+                .into_inner()
+                .next()
+                .ok_or(ParseError::invalid(
+                    "expected comparison operator in filter statement",
+                ))?
+                .as_rule();
+
+            match operator {
+                Rule::eq_operator => Ok(CompositeFilter::eq(name, value)),
+                Rule::not_eq_operator => {
+                    Ok(CompositeFilter::negate(CompositeFilter::eq(name, value)))
+                }
+                Rule::ge_operator => Ok(CompositeFilter::ge(name, value)),
+                Rule::le_operator => Ok(CompositeFilter::le(name, value)),
+                Rule::gt_operator => Ok(CompositeFilter::gt(name, value)),
+                Rule::lt_operator => Ok(CompositeFilter::lt(name, value)),
+                Rule::contains_operator => Ok(CompositeFilter::contains(name, value)),
+                Rule::match_operator => Ok(CompositeFilter::matches(name, value)),
+                Rule::in_operator => Ok(CompositeFilter::in_values(
+                    name,
+                    Self::expect_array(value, &value_pair)?,
+                )),
+                Rule::not_in_operator => Ok(CompositeFilter::negate(CompositeFilter::in_values(
+                    name,
+                    Self::expect_array(value, &value_pair)?,
+                ))),
+                Rule::starts_with_operator => Ok(CompositeFilter::starts_with(name, value)),
+                Rule::substring_operator => Ok(CompositeFilter::substring(name, value)),
+                Rule::like_operator => Ok(CompositeFilter::like(name, value)),
+                Rule::regex_operator => {
+                    // Reject invalid regular expressions early, pointing to the pattern
+                    if let Some(pattern) = value.as_string() {
+                        if Pattern::regex(pattern).is_err() {
+                            return Err(ParseError::invalid_at(
+                                "invalid regular expression in REGEX statement",
+                                &value_pair,
+                            ));
+                        }
+                    }
+
+                    Ok(CompositeFilter::regex(name, value))
+                }
+                _ => Err(ParseError::UnknownOperator),
+            }
+        } else {
+            Err(ParseError::invalid(
+                "expected operator to be a comparison operator in filter statement",
+            ))
+        }
+    }
+
+    fn parse_between_statement(pair: Pair<Rule>) -> Result<CompositeFilter, ParseError> {
+        let statement = pair.clone();
+        let mut inner = pair.into_inner();
+
+        let name = inner
+            .next()
+            .ok_or(ParseError::invalid(
+                "expected property name in BETWEEN statement",
+            ))?
+            .as_str();
+
+        // Skip the `BETWEEN` operator
+        inner.next();
+
+        let first = inner.next().ok_or(ParseError::invalid(
+            "expected lower bound in BETWEEN statement",
+        ))?;
+
+        // Skip the `AND` separating both bounds
+        inner.next();
+
+        let second = inner.next().ok_or(ParseError::invalid(
+            "expected upper bound in BETWEEN statement",
+        ))?;
+
+        let first = Self::parse_value(first);
+        let second = Self::parse_value(second);
+
+        if matches!(first, FieldValue::Array(_)) || matches!(second, FieldValue::Array(_)) {
+            return Err(ParseError::invalid_at(
+                "expected literal values as bounds in BETWEEN statement",
+                &statement,
+            ));
+        }
+
+        // Integers and decimals are both numbers, so they can bound the same range
+        let same_type = std::mem::discriminant(&first) == std::mem::discriminant(&second)
+            || (first.as_number().is_some() && second.as_number().is_some());
+
+        if !same_type {
+            return Err(ParseError::invalid_at(
+                "expected both bounds to have the same type in BETWEEN statement",
+                &statement,
+            ));
+        }
+
+        Ok(CompositeFilter::between(name, first, second))
+    }
+
+    fn parse_null_statement(pair: Pair<Rule>) -> Result<CompositeFilter, ParseError> {
+        let mut inner = pair.into_inner();
+
+        let name = inner
+            .next()
+            .ok_or(ParseError::invalid(
+                "expected property name in filter statement",
+            ))?
+            .as_str();
+
+        let operator = inner
+            .next()
+            .and_then(|operator| operator.into_inner().next())
+            .ok_or(ParseError::invalid(
+                "expected null operator in filter statement",
+            ))?;
+
+        // `IS NOT NULL` and `EXISTS` are equivalent, since null values are not stored
+        match operator.as_rule() {
+            Rule::is_null_operator => Ok(CompositeFilter::is_null(name)),
+            Rule::is_not_null_operator | Rule::exists_operator => Ok(CompositeFilter::exists(name)),
+            _ => Err(ParseError::UnknownOperator),
+        }
+    }
+
+    fn parse_fuzzy_statement(pair: Pair<Rule>) -> Result<CompositeFilter, ParseError> {
+        let mut inner = pair.into_inner();
+
+        let name = inner
+            .next()
+            .ok_or(ParseError::invalid(
+                "expected property name in FUZZY statement",
+            ))?
+            .as_str();
+
+        // Skip the `FUZZY` operator
+        inner.next();
+
+        let value = inner
+            .next()
+            .map(Self::parse_value)
+            .ok_or(ParseError::invalid("expected value in FUZZY statement"))?;
+
+        let max_edits = match inner.next() {
+            Some(edits) => edits.as_str().parse::<u32>().map_err(|_| {
+                ParseError::invalid_at(
+                    "expected a positive whole number of edits in FUZZY statement",
+                    &edits,
+                )
+            })?,
+            None => DEFAULT_FUZZY_EDITS,
+        };
+
+        Ok(CompositeFilter::fuzzy(name, value, max_edits))
+    }
+
+    fn parse_near_statement(pair: Pair<Rule>) -> Result<CompositeFilter, ParseError> {
+        let mut inner = pair.into_inner();
+
+        let name = inner
+            .next()
+            .ok_or(ParseError::invalid(
+                "expected property name in NEAR statement",
+            ))?
+            .as_str();
+
+        // Skip the `NEAR` operator
+        inner.next();
+
+        let value = inner
+            .next()
+            .map(Self::parse_value)
+            .ok_or(ParseError::invalid("expected value in NEAR statement"))?;
+
+        let slop = inner
+            .next()
+            .ok_or(ParseError::invalid("expected distance in NEAR statement"))?;
+        let slop = slop.as_str().parse::<u32>().map_err(|_| {
+            ParseError::invalid_at(
+                "expected a positive whole number as distance in NEAR statement",
+                &slop,
+            )
+        })?;
+
+        let ordered = inner.next().is_some();
+
+        Ok(CompositeFilter::near(name, value, slop, ordered))
+    }
+
+    fn expect_array(value: FieldValue, pair: &Pair<Rule>) -> Result<Vec<FieldValue>, ParseError> {
+        match value {
+            FieldValue::Array(values) => Ok(values),
+            _ => Err(ParseError::invalid_at(
+                "expected array value for IN operator in filter statement",
+                pair,
+            )),
+        }
+    }
+
+    /// Join two filters with a logical conjunction. Any side being already a conjunction
+    /// is flattened, so that chains such as `a AND b AND c` result in a single `And`.
+    fn join_and(left: CompositeFilter, right: CompositeFilter) -> CompositeFilter {
+        let mut filters = Vec::new();
+
+        for filter in [left, right] {
+            match filter {
+                CompositeFilter::And(inner) => filters.extend(inner),
+                filter => filters.push(filter),
+            }
+        }
+
+        CompositeFilter::And(filters)
+    }
+
+    /// Join two filters with a logical disjunction. Any side being already a disjunction
+    /// is flattened, so that chains such as `a OR b OR c` result in a single `Or`.
+    fn join_or(left: CompositeFilter, right: CompositeFilter) -> CompositeFilter {
+        let mut filters = Vec::new();
+
+        for filter in [left, right] {
+            match filter {
+                CompositeFilter::Or(inner) => filters.extend(inner),
+                filter => filters.push(filter),
+            }
+        }
+
+        CompositeFilter::Or(filters)
+    }
+
+    fn parse_value(pair: Pair<Rule>) -> FieldValue {
+        match pair.as_rule() {
+            Rule::WHITESPACE
+            | Rule::NAME_CHAR
             | Rule::name
             | Rule::char
             | Rule::date
@@ -891,11 +3146,40 @@ impl QueryParser {
             | Rule::lt_operator
             | Rule::contains_operator
             | Rule::match_operator
+            | Rule::in_operator
+            | Rule::not_in_operator
+            | Rule::starts_with_operator
+            | Rule::substring_operator
+            | Rule::like_operator
+            | Rule::regex_operator
+            | Rule::and_operator
+            | Rule::or_operator
             | Rule::logical_operator
+            | Rule::not_operator
+            | Rule::between_operator
+            | Rule::is_null_operator
+            | Rule::is_not_null_operator
+            | Rule::exists_operator
+            | Rule::null_operator
+            | Rule::fuzzy_operator
+            | Rule::fuzzy_statement
+            | Rule::near_operator
+            | Rule::ORDERED
+            | Rule::near_statement
             | Rule::statement
+            | Rule::between_statement
+            | Rule::null_statement
+            | Rule::group
+            | Rule::primary
+            | Rule::term
             | Rule::composite
+            | Rule::EXPLAIN
+            | Rule::SELECT
             | Rule::FROM
             | Rule::WHERE
+            | Rule::BOOST
+            | Rule::HIGHLIGHT
+            | Rule::boost
             | Rule::ORDER_BY
             | Rule::LIMIT
             | Rule::OFFSET
@@ -903,7 +3187,21 @@ impl QueryParser {
             | Rule::BRANCH
             | Rule::ASC
             | Rule::DESC
-            | Rule::query => unreachable!(),
+            | Rule::NULLS_FIRST
+            | Rule::NULLS_LAST
+            | Rule::sort_key
+            | Rule::count_function
+            | Rule::sum_function
+            | Rule::avg_function
+            | Rule::min_function
+            | Rule::max_function
+            | Rule::aggregate_function
+            | Rule::all_fields
+            | Rule::aggregation
+            | Rule::GROUP_BY
+            | Rule::AGG
+            | Rule::query
+            | Rule::EOI => unreachable!(),
             Rule::value => {
                 let value = pair
                     .into_inner()
@@ -912,19 +3210,22 @@ impl QueryParser {
                 Self::parse_value(value)
             }
             Rule::number => {
-                let value = pair
-                    .as_str()
-                    .parse()
-                    .expect("Numeric value could not be parsed as f64.");
-                FieldValue::dec(value)
+                // Numbers without a fraction or an exponent are integers, as in JSON
+                let value = pair.as_str();
+                match value.parse() {
+                    Ok(integer) => FieldValue::Integer(integer),
+                    Err(_) => FieldValue::dec(
+                        value
+                            .parse()
+                            .expect("Numeric value could not be parsed as f64."),
+                    ),
+                }
             }
             Rule::string => {
-                let value = pair
-                    .as_str()
-                    // Remove double quotes from beginning and end (as stated in the grammar)
-                    .trim_start_matches('"')
-                    .trim_end_matches('"');
-                FieldValue::str(value)
+                let value = pair.as_str();
+                // Remove double quotes from beginning and end (as stated in the grammar)
+                let value = &value[1..value.len() - 1];
+                FieldValue::String(Self::unescape(value))
             }
             Rule::boolean => {
                 let value = pair
@@ -940,63 +3241,967 @@ impl QueryParser {
         }
     }
 
-    /// Normalizes an input query to remove duplicate whitespaces, tabs and new lines
-    fn normalize(input: &str) -> String {
-        let mut result = String::new();
-        let mut prev_was_whitespace = false;
+    /// Replace the escape sequences allowed in string values by the escaped characters.
+    fn unescape(value: &str) -> String {
+        let mut result = String::with_capacity(value.len());
+        let mut chars = value.chars();
 
-        for c in input.chars() {
-            if c.is_whitespace() {
-                if !prev_was_whitespace {
-                    result.push(' ');
-                    prev_was_whitespace = true;
-                }
-            } else {
+        while let Some(c) = chars.next() {
+            if c != '\\' {
                 result.push(c);
-                prev_was_whitespace = false;
+                continue;
+            }
+
+            match chars.next() {
+                Some('b') => result.push('\u{8}'),
+                Some('f') => result.push('\u{c}'),
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some('t') => result.push('\t'),
+                Some('u') => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    let escaped = u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    result.push(escaped);
+                }
+                Some(escaped) => result.push(escaped),
+                None => result.push(c),
+            }
+        }
+
+        result
+    }
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum QueryError {
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[error(transparent)]
+    Index(#[from] IndexError),
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ParseError {
+    #[error("query is defined but empty")]
+    EmptyQuery,
+    #[error("invalid query \"{message}\"")]
+    InvalidQuery {
+        message: &'static str,
+        span: Option<ErrorSpan>,
+    },
+    #[error(
+        "unexpected input at line {}, column {}, expected one of: {}",
+        .span.start.line,
+        .span.start.column,
+        .expected.join(", ")
+    )]
+    Syntax {
+        span: ErrorSpan,
+        expected: Vec<String>,
+    },
+    #[error("query contains unknown operator")]
+    UnknownOperator,
+}
+
+impl ParseError {
+    pub(crate) fn invalid(message: &'static str) -> Self {
+        ParseError::InvalidQuery {
+            message,
+            span: None,
+        }
+    }
+
+    pub(crate) fn invalid_at(message: &'static str, pair: &Pair<Rule>) -> Self {
+        ParseError::InvalidQuery {
+            message,
+            span: Some(ErrorSpan::from_pair(pair)),
+        }
+    }
+
+    fn from_pest(error: pest::error::Error<Rule>) -> Self {
+        let span = match error.line_col {
+            LineColLocation::Pos((line, column)) => ErrorSpan {
+                start: ErrorPosition { line, column },
+                end: ErrorPosition { line, column },
+            },
+            LineColLocation::Span((line, column), (end_line, end_column)) => ErrorSpan {
+                start: ErrorPosition { line, column },
+                end: ErrorPosition {
+                    line: end_line,
+                    column: end_column,
+                },
+            },
+        };
+
+        let expected = match error.variant {
+            ErrorVariant::ParsingError { positives, .. } => {
+                let mut expected: Vec<String> = positives
+                    .iter()
+                    .map(|rule| describe_rule(rule).to_string())
+                    .collect();
+                expected.dedup();
+                expected
             }
+            ErrorVariant::CustomError { message } => vec![message],
+        };
+
+        ParseError::Syntax { span, expected }
+    }
+
+    /// A stable code identifying the kind of error, independent of its message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::EmptyQuery => "empty_query",
+            ParseError::InvalidQuery { .. } => "invalid_query",
+            ParseError::Syntax { .. } => "syntax_error",
+            ParseError::UnknownOperator => "unknown_operator",
+        }
+    }
+
+    /// The location of the error in the query, if known.
+    pub fn span(&self) -> Option<&ErrorSpan> {
+        match self {
+            ParseError::InvalidQuery { span, .. } => span.as_ref(),
+            ParseError::Syntax { span, .. } => Some(span),
+            _ => None,
+        }
+    }
+
+    /// The tokens that were expected at the location of a syntax error.
+    pub fn expected(&self) -> &[String] {
+        match self {
+            ParseError::Syntax { expected, .. } => expected,
+            _ => &[],
+        }
+    }
+
+    /// Render the error together with the line of the `input` query where it occurred,
+    /// pointing at its location with carets, e.g.:
+    ///
+    /// ```text
+    /// unexpected input at line 1, column 25, expected one of: AND, OR, GROUP BY, ...
+    ///   |
+    /// 1 | FROM players WHERE a = 1 XOR b = 2
+    ///   |                          ^
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        let mut output = self.to_string();
+
+        let Some(span) = self.span() else {
+            return output;
+        };
+
+        let Some(line) = input.lines().nth(span.start.line.saturating_sub(1)) else {
+            return output;
+        };
+
+        let offset = span.start.column.saturating_sub(1);
+        let width = if span.end.line == span.start.line {
+            span.end.column.saturating_sub(span.start.column)
+        } else {
+            line.chars().count().saturating_sub(offset)
+        };
+
+        let number = span.start.line.to_string();
+        let padding = " ".repeat(number.len());
+
+        output.push_str(&format!(
+            "\n{padding} |\n{number} | {line}\n{padding} | {}{}",
+            " ".repeat(offset),
+            "^".repeat(width.max(1))
+        ));
+
+        output
+    }
+}
+
+/// A position in a query, with 1-based line and column.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ErrorPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// The range of a query where an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ErrorSpan {
+    pub start: ErrorPosition,
+    pub end: ErrorPosition,
+}
+
+impl ErrorSpan {
+    fn from_pair(pair: &Pair<Rule>) -> Self {
+        let span = pair.as_span();
+        let (line, column) = span.start_pos().line_col();
+        let (end_line, end_column) = span.end_pos().line_col();
+
+        ErrorSpan {
+            start: ErrorPosition { line, column },
+            end: ErrorPosition {
+                line: end_line,
+                column: end_column,
+            },
         }
+    }
+}
+
+/// Describe a grammar rule as the token expected in the query.
+fn describe_rule(rule: &Rule) -> &'static str {
+    match rule {
+        Rule::EOI => "end of query",
+        Rule::name => "name",
+        Rule::value => "value",
+        Rule::number => "number",
+        Rule::string => "string",
+        Rule::boolean => "boolean",
+        Rule::array => "array",
+        Rule::date => "date",
+        Rule::comparison_operator => "comparison operator",
+        Rule::eq_operator => "=",
+        Rule::not_eq_operator => "!=",
+        Rule::ge_operator => ">=",
+        Rule::le_operator => "<=",
+        Rule::gt_operator => ">",
+        Rule::lt_operator => "<",
+        Rule::contains_operator => "CONTAINS",
+        Rule::match_operator => "MATCH",
+        Rule::in_operator => "IN",
+        Rule::not_in_operator => "NOT IN",
+        Rule::starts_with_operator => "STARTS WITH",
+        Rule::substring_operator => "SUBSTRING",
+        Rule::like_operator => "LIKE",
+        Rule::regex_operator => "REGEX",
+        Rule::and_operator => "AND",
+        Rule::or_operator => "OR",
+        Rule::not_operator => "NOT",
+        Rule::between_operator => "BETWEEN",
+        Rule::is_null_operator => "IS NULL",
+        Rule::is_not_null_operator => "IS NOT NULL",
+        Rule::exists_operator => "EXISTS",
+        Rule::null_operator => "null operator",
+        Rule::fuzzy_operator => "FUZZY",
+        Rule::near_operator => "NEAR",
+        Rule::ORDERED => "ORDERED",
+        Rule::statement
+        | Rule::between_statement
+        | Rule::null_statement
+        | Rule::fuzzy_statement
+        | Rule::near_statement
+        | Rule::composite => "filter",
+        Rule::group => "(",
+        Rule::EXPLAIN => "EXPLAIN",
+        Rule::SELECT => "SELECT",
+        Rule::FROM => "FROM",
+        Rule::WHERE => "WHERE",
+        Rule::BOOST => "BOOST",
+        Rule::HIGHLIGHT => "HIGHLIGHT",
+        Rule::boost => "boost",
+        Rule::GROUP_BY => "GROUP BY",
+        Rule::AGG => "AGG",
+        Rule::ORDER_BY => "ORDER BY",
+        Rule::LIMIT => "LIMIT",
+        Rule::OFFSET => "OFFSET",
+        Rule::AS_OF => "AS OF",
+        Rule::BRANCH => "BRANCH",
+        Rule::ASC => "ASC",
+        Rule::DESC => "DESC",
+        Rule::NULLS_FIRST => "NULLS FIRST",
+        Rule::NULLS_LAST => "NULLS LAST",
+        Rule::sort_key => "sort field",
+        Rule::aggregation => "aggregation",
+        Rule::aggregate_function
+        | Rule::count_function
+        | Rule::sum_function
+        | Rule::avg_function
+        | Rule::min_function
+        | Rule::max_function => "aggregate function",
+        Rule::all_fields => "*",
+        _ => "token",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use time::{Date, Month};
+
+    use crate::data::FieldValue;
+
+    use crate::query::{
+        build_snippet, AggregateFunction, Aggregation, Boost, CompositeFilter, DeltaScope,
+        ErrorPosition, ErrorSpan, NullsPlacement, Pagination, ParseError, Query, QueryParser, Sort,
+        SortDirection, ALL_FIELDS, DEFAULT_PAGE_SIZE, DEFAULT_START_PAGE, SCORE_FIELD,
+    };
+
+    #[test]
+    fn creates_skeleton_query() {
+        // given
+        let input = "FROM person";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Query {
+                explain: false,
+                entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: None,
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
+                scope: None,
+                pagination: Pagination::default()
+            }
+        )
+    }
+
+    #[test]
+    fn creates_string_filter() {
+        // given
+        let input = "FROM person WHERE person.name = \"David\"";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Query {
+                explain: false,
+                entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
+                scope: None,
+                pagination: Pagination::default()
+            }
+        )
+    }
+
+    #[test]
+    fn creates_date_filter() {
+        // given
+        let input = "FROM person WHERE person.birth_date < \"2020-01-01\"";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Query {
+                explain: false,
+                entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::lt(
+                    "person.birth_date",
+                    FieldValue::str("2020-01-01")
+                )),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
+                scope: None,
+                pagination: Pagination::default()
+            }
+        )
+    }
+
+    #[test]
+    fn creates_contains_filter() {
+        // given
+        let input = "FROM person WHERE person.name contains \"Alice\"";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Query {
+                explain: false,
+                entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::contains(
+                    "person.name",
+                    FieldValue::str("Alice")
+                )),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
+                scope: None,
+                pagination: Pagination::default()
+            }
+        )
+    }
+
+    #[test]
+    fn creates_match_filter() {
+        // given
+        let input = "FROM person WHERE person.name MATCH \"Alice Bob\"";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Query {
+                explain: false,
+                entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::matches(
+                    "person.name",
+                    FieldValue::str("Alice Bob")
+                )),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
+                scope: None,
+                pagination: Pagination::default()
+            }
+        )
+    }
+
+    #[test]
+    fn creates_complex_filter() {
+        // given
+        let input = r#"
+            FROM person
+                WHERE (person.name != "Michael Jordan" AND person.address CONTAINS "Street")
+                    AND (score > 1 OR active = true AND (person.name.simple = "Roger" OR score <= 5))
+        "#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Query {
+                explain: false,
+                entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::and(vec![
+                    CompositeFilter::negate(CompositeFilter::eq(
+                        "person.name",
+                        FieldValue::str("Michael Jordan")
+                    )),
+                    CompositeFilter::contains("person.address", FieldValue::str("Street")),
+                    CompositeFilter::or(vec![
+                        CompositeFilter::gt("score", FieldValue::int(1)),
+                        CompositeFilter::and(vec![
+                            CompositeFilter::eq("active", FieldValue::bool(true)),
+                            CompositeFilter::or(vec![
+                                CompositeFilter::eq("person.name.simple", FieldValue::str("Roger")),
+                                CompositeFilter::le("score", FieldValue::int(5)),
+                            ])
+                        ])
+                    ])
+                ])),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
+                scope: None,
+                pagination: Pagination::default()
+            }
+        )
+    }
+
+    #[test]
+    fn creates_filter_with_and_precedence_over_or() {
+        // given
+        let input = "FROM person WHERE a = 1 OR b = 2 AND c = 3 OR d = 4";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::or(vec![
+                CompositeFilter::eq("a", FieldValue::int(1)),
+                CompositeFilter::and(vec![
+                    CompositeFilter::eq("b", FieldValue::int(2)),
+                    CompositeFilter::eq("c", FieldValue::int(3)),
+                ]),
+                CompositeFilter::eq("d", FieldValue::int(4)),
+            ]))
+        )
+    }
+
+    #[test]
+    fn creates_filter_with_flattened_and_chain() {
+        // given
+        let input = "FROM person WHERE a = 1 AND b = 2 AND c = 3 OR d = 4";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::or(vec![
+                CompositeFilter::and(vec![
+                    CompositeFilter::eq("a", FieldValue::int(1)),
+                    CompositeFilter::eq("b", FieldValue::int(2)),
+                    CompositeFilter::eq("c", FieldValue::int(3)),
+                ]),
+                CompositeFilter::eq("d", FieldValue::int(4)),
+            ]))
+        )
+    }
+
+    #[test]
+    fn creates_filter_with_case_insensitive_operators() {
+        // given
+        let input = "FROM person WHERE (a = 1 or b = 2) and c = 3 Order By a";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::and(vec![
+                CompositeFilter::or(vec![
+                    CompositeFilter::eq("a", FieldValue::int(1)),
+                    CompositeFilter::eq("b", FieldValue::int(2)),
+                ]),
+                CompositeFilter::eq("c", FieldValue::int(3)),
+            ]))
+        );
+        assert_eq!(result.sort, vec![Sort::new("a")]);
+    }
+
+    #[test]
+    fn creates_negated_group_filter() {
+        // given
+        let input = r#"FROM person WHERE NOT (sport = "Football" OR active = false)"#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::negate(CompositeFilter::or(vec![
+                CompositeFilter::eq("sport", FieldValue::str("Football")),
+                CompositeFilter::eq("active", FieldValue::bool(false)),
+            ])))
+        )
+    }
+
+    #[test]
+    fn creates_negated_statement_with_precedence_over_and() {
+        // given
+        let input = "FROM person WHERE not a = 1 AND NOT NOT b = 2 OR notes = 3";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::or(vec![
+                CompositeFilter::and(vec![
+                    CompositeFilter::negate(CompositeFilter::eq("a", FieldValue::int(1))),
+                    CompositeFilter::negate(CompositeFilter::negate(CompositeFilter::eq(
+                        "b",
+                        FieldValue::int(2)
+                    ))),
+                ]),
+                CompositeFilter::eq("notes", FieldValue::int(3)),
+            ]))
+        )
+    }
+
+    #[test]
+    fn creates_in_filter() {
+        // given
+        let input =
+            r#"FROM person WHERE sport IN ["Football", "Basketball"] AND score not in [1, 2]"#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::and(vec![
+                CompositeFilter::in_values(
+                    "sport",
+                    vec![FieldValue::str("Football"), FieldValue::str("Basketball")]
+                ),
+                CompositeFilter::negate(CompositeFilter::in_values(
+                    "score",
+                    vec![FieldValue::int(1), FieldValue::int(2)]
+                )),
+            ]))
+        )
+    }
+
+    #[test]
+    fn rejects_in_filter_without_array() {
+        // given
+        let input = r#"FROM person WHERE sport IN "Football""#;
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        assert_eq!(
+            result,
+            Err(ParseError::InvalidQuery {
+                message: "expected array value for IN operator in filter statement",
+                span: Some(ErrorSpan {
+                    start: ErrorPosition {
+                        line: 1,
+                        column: 28
+                    },
+                    end: ErrorPosition {
+                        line: 1,
+                        column: 38
+                    }
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn creates_between_filter() {
+        // given
+        let input = r#"
+            FROM person
+                WHERE score BETWEEN 1 AND 5.5 AND birth_date between "2000-01-01" and "2010-01-01"
+        "#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::and(vec![
+                CompositeFilter::between("score", FieldValue::int(1), FieldValue::dec(5.5)),
+                CompositeFilter::between(
+                    "birth_date",
+                    FieldValue::str("2000-01-01"),
+                    FieldValue::str("2010-01-01")
+                ),
+            ]))
+        )
+    }
+
+    #[test]
+    fn rejects_between_filter_with_different_bound_types() {
+        // given
+        let input = r#"FROM person WHERE score BETWEEN 1 AND "5""#;
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        assert_eq!(
+            result,
+            Err(ParseError::InvalidQuery {
+                message: "expected both bounds to have the same type in BETWEEN statement",
+                span: Some(ErrorSpan {
+                    start: ErrorPosition {
+                        line: 1,
+                        column: 19
+                    },
+                    end: ErrorPosition {
+                        line: 1,
+                        column: 42
+                    }
+                })
+            })
+        )
+    }
+
+    #[test]
+    fn creates_starts_with_filter() {
+        // given
+        let input = "FROM person WHERE name starts   with \"Mic\" AND name BETWEEN \"A\" AND \"F\"";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        let filter = result.filter.unwrap();
+        assert_eq!(
+            filter,
+            CompositeFilter::and(vec![
+                CompositeFilter::starts_with("name", FieldValue::str("Mic")),
+                CompositeFilter::between("name", FieldValue::str("A"), FieldValue::str("F")),
+            ])
+        );
+        assert_eq!(
+            filter.to_string(),
+            "name STARTS WITH \"Mic\" AND name BETWEEN \"A\" AND \"F\""
+        );
+    }
+
+    #[test]
+    fn creates_pattern_filters() {
+        // given
+        let input = r#"FROM person WHERE code LIKE "AB_%" OR code REGEX "^\\d+$""#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        let filter = result.filter.unwrap();
+        assert_eq!(
+            filter,
+            CompositeFilter::or(vec![
+                CompositeFilter::like("code", FieldValue::str("AB_%")),
+                CompositeFilter::regex("code", FieldValue::str("^\\d+$")),
+            ])
+        );
+        assert_eq!(
+            filter.to_string(),
+            r#"code LIKE "AB_%" OR code REGEX "^\\d+$""#
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_regex() {
+        // given
+        let input = r#"FROM person WHERE code REGEX "[A-""#;
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        let error = result.unwrap_err();
+        assert_eq!(error.code(), "invalid_query");
+        assert_eq!(
+            error.span(),
+            Some(&ErrorSpan {
+                start: ErrorPosition {
+                    line: 1,
+                    column: 30
+                },
+                end: ErrorPosition {
+                    line: 1,
+                    column: 35
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn creates_fuzzy_filter() {
+        // given
+        let input = r#"FROM person WHERE name FUZZY "Ronaldoo" AND name fuzzy("Mesi", 1)"#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        let filter = result.filter.unwrap();
+        assert_eq!(
+            filter,
+            CompositeFilter::and(vec![
+                CompositeFilter::fuzzy("name", FieldValue::str("Ronaldoo"), 2),
+                CompositeFilter::fuzzy("name", FieldValue::str("Mesi"), 1),
+            ])
+        );
+        assert_eq!(
+            filter.to_string(),
+            r#"name FUZZY("Ronaldoo", 2) AND name FUZZY("Mesi", 1)"#
+        );
+    }
+
+    #[test]
+    fn rejects_fuzzy_filter_with_decimal_edits() {
+        // given
+        let input = r#"FROM person WHERE name FUZZY("Mesi", 1.5)"#;
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        assert_eq!(result.unwrap_err().code(), "invalid_query");
+    }
+
+    #[test]
+    fn creates_near_filter() {
+        // given
+        let input = r#"FROM person WHERE description NEAR("delta storage", 3) OR description near("search engine", 1, ordered)"#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        let filter = result.filter.unwrap();
+        assert_eq!(
+            filter,
+            CompositeFilter::or(vec![
+                CompositeFilter::near("description", FieldValue::str("delta storage"), 3, false),
+                CompositeFilter::near("description", FieldValue::str("search engine"), 1, true),
+            ])
+        );
+        assert_eq!(
+            filter.to_string(),
+            r#"description NEAR("delta storage", 3) OR description NEAR("search engine", 1, ORDERED)"#
+        );
+    }
+
+    #[test]
+    fn rejects_near_filter_without_distance() {
+        // given
+        let input = r#"FROM person WHERE description NEAR("delta storage")"#;
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        assert_eq!(result.unwrap_err().code(), "syntax_error");
+    }
+
+    #[test]
+    fn creates_null_filters() {
+        // given
+        let input = "FROM person WHERE score IS NULL OR name is not null AND age EXISTS";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        let filter = result.filter.unwrap();
+        assert_eq!(
+            filter,
+            CompositeFilter::or(vec![
+                CompositeFilter::is_null("score"),
+                CompositeFilter::and(vec![
+                    CompositeFilter::exists("name"),
+                    CompositeFilter::exists("age"),
+                ]),
+            ])
+        );
+        assert_eq!(
+            filter.to_string(),
+            "score IS NULL OR name EXISTS AND age EXISTS"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_trailing_input() {
+        // given
+        let input = "FROM person WHERE a = 1 XOR b = 2";
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_syntax_error_with_position_and_expected_tokens() {
+        // given
+        let input = "FORM players";
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        let error = result.unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::Syntax {
+                span: ErrorSpan {
+                    start: ErrorPosition { line: 1, column: 1 },
+                    end: ErrorPosition { line: 1, column: 1 }
+                },
+                expected: vec![
+                    "EXPLAIN".to_string(),
+                    "SELECT".to_string(),
+                    "FROM".to_string()
+                ]
+            }
+        );
+        assert_eq!(error.code(), "syntax_error");
+        assert_eq!(
+            error.render(input),
+            "unexpected input at line 1, column 1, expected one of: EXPLAIN, SELECT, FROM\n  |\n1 | FORM players\n  | ^"
+        );
+    }
+
+    #[test]
+    fn rejects_syntax_error_in_multiline_query() {
+        // given
+        let input = "FROM players\n    WHERE score >\n    ORDER BY score";
+
+        // when
+        let result = QueryParser::parse_query(input);
 
-        result.trim().to_string()
+        // then
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.span(),
+            Some(&ErrorSpan {
+                start: ErrorPosition { line: 3, column: 5 },
+                end: ErrorPosition { line: 3, column: 5 }
+            })
+        );
+        assert_eq!(error.expected(), &["value".to_string()]);
+        assert_eq!(
+            error.render(input),
+            "unexpected input at line 3, column 5, expected one of: value\n  |\n3 |     ORDER BY score\n  |     ^"
+        );
     }
-}
-
-#[derive(Error, Debug)]
-#[non_exhaustive]
-pub enum QueryError {
-    #[error(transparent)]
-    Filter(#[from] FilterError),
-    #[error(transparent)]
-    Storage(#[from] StorageError),
-}
 
-#[derive(Error, Debug, PartialEq)]
-#[non_exhaustive]
-pub enum ParseError {
-    #[error("query is defined but empty")]
-    EmptyQuery,
-    #[error("invalid query \"{0}\"")]
-    InvalidQuery(&'static str),
-    #[error("query could not be parsed for line \"{0}\"")]
-    QueryParse(String),
-    #[error("query contains unknown operator")]
-    UnknownOperator,
-}
+    #[test]
+    fn renders_invalid_query_span() {
+        // given
+        let input = r#"FROM person WHERE sport IN "Football""#;
 
-#[cfg(test)]
-mod tests {
-    use time::{Date, Month};
+        // when
+        let error = QueryParser::parse_query(input).unwrap_err();
 
-    use crate::data::FieldValue;
-    use crate::query::{
-        CompositeFilter, DeltaScope, Pagination, ParsedQuery, QueryParser, Sort, SortDirection,
-        DEFAULT_PAGE_SIZE, DEFAULT_START_PAGE,
-    };
+        // then
+        assert_eq!(error.code(), "invalid_query");
+        assert_eq!(
+            error.render(input),
+            r#"invalid query "expected array value for IN operator in filter statement"
+  |
+1 | FROM person WHERE sport IN "Football"
+  |                            ^^^^^^^^^^"#
+        );
+    }
 
     #[test]
-    fn creates_skeleton_query() {
+    fn creates_select_projection() {
         // given
-        let input = "FROM person";
+        let input = "SELECT name, person.score FROM person WHERE person.name = \"David\"";
 
         // when
         let result = QueryParser::parse_query(input).unwrap();
@@ -1004,10 +4209,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
-                filter: None,
-                sort: None,
+                projection: Some(vec!["name".to_string(), "person.score".to_string()]),
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
                 scope: None,
                 pagination: Pagination::default()
             }
@@ -1015,9 +4226,9 @@ mod tests {
     }
 
     #[test]
-    fn creates_string_filter() {
+    fn creates_explained_query() {
         // given
-        let input = "FROM person WHERE person.name = \"David\"";
+        let input = "explain SELECT name FROM person WHERE score > 1";
 
         // when
         let result = QueryParser::parse_query(input).unwrap();
@@ -1025,20 +4236,30 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: true,
                 entity: "person".to_string(),
-                filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: None,
+                projection: Some(vec!["name".to_string()]),
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::gt("score", FieldValue::int(1))),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![],
                 scope: None,
                 pagination: Pagination::default()
             }
-        )
+        );
+        assert_eq!(
+            result.to_string(),
+            "EXPLAIN SELECT name FROM person WHERE score > 1"
+        );
     }
 
     #[test]
-    fn creates_date_filter() {
+    fn creates_filter_order_by() {
         // given
-        let input = "FROM person WHERE person.birth_date < \"2020-01-01\"";
+        let input = "FROM person WHERE person.name = \"David\" ORDER BY person.score";
 
         // when
         let result = QueryParser::parse_query(input).unwrap();
@@ -1046,132 +4267,148 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
-                filter: Some(CompositeFilter::lt(
-                    "person.birth_date",
-                    FieldValue::str("2020-01-01")
-                )),
-                sort: None,
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
                 scope: None,
+                sort: vec![Sort::new("person.score")],
                 pagination: Pagination::default()
             }
         )
     }
 
     #[test]
-    fn creates_contains_filter() {
+    fn creates_filter_order_by_multiple_keys() {
         // given
-        let input = "FROM person WHERE person.name contains \"Alice\"";
+        let input = "FROM person ORDER BY sport ASC, score DESC NULLS FIRST, name nulls last";
 
         // when
         let result = QueryParser::parse_query(input).unwrap();
 
         // then
         assert_eq!(
-            result,
-            ParsedQuery {
-                entity: "person".to_string(),
-                filter: Some(CompositeFilter::contains(
-                    "person.name",
-                    FieldValue::str("Alice")
-                )),
-                sort: None,
-                scope: None,
-                pagination: Pagination::default()
-            }
+            result.sort,
+            vec![
+                Sort::new("sport").with_direction(SortDirection::ASC),
+                Sort::new("score")
+                    .with_direction(SortDirection::DESC)
+                    .with_nulls(NullsPlacement::First),
+                Sort::new("name").with_nulls(NullsPlacement::Last),
+            ]
         )
     }
 
     #[test]
-    fn creates_match_filter() {
+    fn creates_all_fields_filter_with_boosts() {
         // given
-        let input = "FROM person WHERE person.name MATCH \"Alice Bob\"";
+        let input = r#"FROM person WHERE _all MATCH "lionel messi" BOOST name^2, description^0.5 ORDER BY _score DESC"#;
 
         // when
         let result = QueryParser::parse_query(input).unwrap();
 
         // then
         assert_eq!(
-            result,
-            ParsedQuery {
-                entity: "person".to_string(),
-                filter: Some(CompositeFilter::matches(
-                    "person.name",
-                    FieldValue::str("Alice Bob")
-                )),
-                sort: None,
-                scope: None,
-                pagination: Pagination::default()
-            }
-        )
+            result.filter,
+            Some(CompositeFilter::matches(
+                ALL_FIELDS,
+                FieldValue::str("lionel messi")
+            ))
+        );
+        assert_eq!(
+            result.boosts,
+            vec![Boost::new("name", 2.0), Boost::new("description", 0.5)]
+        );
+        assert_eq!(result.to_string(), input);
     }
 
     #[test]
-    fn creates_complex_filter() {
+    fn creates_substring_filter() {
         // given
-        let input = r#"
-            FROM person
-                WHERE (person.name != "Michael Jordan" AND person.address CONTAINS "Street")
-                    AND (score > 1 OR active = true AND (person.name.simple = "Roger" OR score <= 5))
-        "#;
+        let input = r#"FROM products WHERE code SUBSTRING "X12" AND name CONTAINS "substring""#;
 
         // when
         let result = QueryParser::parse_query(input).unwrap();
 
         // then
         assert_eq!(
-            result,
-            ParsedQuery {
-                entity: "person".to_string(),
-                filter: Some(CompositeFilter::and(vec![
-                    CompositeFilter::negate(CompositeFilter::eq(
-                        "person.name",
-                        FieldValue::str("Michael Jordan")
-                    )),
-                    CompositeFilter::and(vec![
-                        CompositeFilter::contains("person.address", FieldValue::str("Street")),
-                        CompositeFilter::or(vec![
-                            CompositeFilter::gt("score", FieldValue::dec(1.0)),
-                            CompositeFilter::and(vec![
-                                CompositeFilter::eq("active", FieldValue::bool(true)),
-                                CompositeFilter::or(vec![
-                                    CompositeFilter::eq(
-                                        "person.name.simple",
-                                        FieldValue::str("Roger")
-                                    ),
-                                    CompositeFilter::le("score", FieldValue::dec(5.0)),
-                                ])
-                            ])
-                        ])
-                    ])
-                ])),
-                sort: None,
-                scope: None,
-                pagination: Pagination::default()
-            }
-        )
+            result.filter,
+            Some(CompositeFilter::And(vec![
+                CompositeFilter::substring("code", FieldValue::str("X12")),
+                CompositeFilter::contains("name", FieldValue::str("substring")),
+            ]))
+        );
+        assert_eq!(result.to_string(), input);
     }
 
     #[test]
-    fn creates_filter_order_by() {
+    fn creates_filter_with_highlight() {
         // given
-        let input = "FROM person WHERE person.name = \"David\" ORDER BY person.score";
+        let input = r#"FROM person WHERE description CONTAINS "delta" HIGHLIGHT name, description ORDER BY _score DESC"#;
 
         // when
         let result = QueryParser::parse_query(input).unwrap();
 
         // then
         assert_eq!(
-            result,
-            ParsedQuery {
-                entity: "person".to_string(),
-                filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                scope: None,
-                sort: Some(Sort::new("person.score")),
-                pagination: Pagination::default()
-            }
-        )
+            result.highlight,
+            vec!["name".to_string(), "description".to_string()]
+        );
+        assert_eq!(result.to_string(), input);
+    }
+
+    #[test]
+    fn builds_highlight_snippet() {
+        // given
+        let value = "Writing every change to the data stored on disk is slow, so <deltas> & other changes are applied in memory on top of the stored data before searching it";
+        let delta = 0..5;
+
+        // when
+        let snippet = build_snippet(value, &[61..67, 100..106]);
+        let short = build_snippet("Delta search", &[delta]);
+
+        // then
+        assert_eq!(
+            snippet,
+            "…to the data stored on disk is slow, so &lt;<em>deltas</em>&gt; &amp; other changes are applied in <em>memory</em> on top of the stored data before…"
+        );
+        assert_eq!(short, "<em>Delta</em> search");
+    }
+
+    #[test]
+    fn rejects_negative_boost() {
+        // given
+        let input = r#"FROM person WHERE _all MATCH "lionel messi" BOOST name^-2"#;
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        assert_eq!(result.unwrap_err().code(), "invalid_query");
+    }
+
+    #[test]
+    fn creates_filter_order_by_score() {
+        // given
+        let input = "FROM person WHERE description MATCH \"delta search\" ORDER BY _score DESC";
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.sort,
+            vec![Sort::new(SCORE_FIELD).with_direction(SortDirection::DESC)]
+        );
+        assert_eq!(
+            result.to_string(),
+            "FROM person WHERE description MATCH \"delta search\" ORDER BY _score DESC"
+        );
     }
 
     #[test]
@@ -1185,10 +4422,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::DESC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::DESC)],
                 scope: None,
                 pagination: Pagination::default()
             }
@@ -1206,10 +4449,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::DESC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::DESC)],
                 scope: None,
                 pagination: Pagination::new(DEFAULT_START_PAGE, 10)
             }
@@ -1227,10 +4476,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::ASC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::ASC)],
                 scope: None,
                 pagination: Pagination::new(10, DEFAULT_PAGE_SIZE)
             }
@@ -1249,10 +4504,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::ASC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::ASC)],
                 scope: None,
                 pagination: Pagination::new(10, 20)
             }
@@ -1271,10 +4532,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::ASC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::ASC)],
                 scope: None,
                 pagination: Pagination::new(10, 20)
             }
@@ -1293,10 +4560,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::ASC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::ASC)],
                 scope: Some(DeltaScope {
                     date: Date::from_calendar_date(2020, Month::January, 1).unwrap(),
                     branch: None
@@ -1324,10 +4597,16 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::eq("person.name", FieldValue::str("David"))),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::ASC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::ASC)],
                 scope: Some(DeltaScope {
                     date: Date::from_calendar_date(2020, Month::January, 1).unwrap(),
                     branch: Some(1)
@@ -1355,19 +4634,20 @@ mod tests {
         // then
         assert_eq!(
             result,
-            ParsedQuery {
+            Query {
+                explain: false,
                 entity: "person".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
                 filter: Some(CompositeFilter::or(vec![
                     CompositeFilter::eq("person.name", FieldValue::str("David")),
-                    CompositeFilter::or(vec![
-                        CompositeFilter::contains("person.address", FieldValue::str("Street")),
-                        CompositeFilter::matches(
-                            "person.description",
-                            FieldValue::str("nice person")
-                        )
-                    ])
+                    CompositeFilter::contains("person.address", FieldValue::str("Street")),
+                    CompositeFilter::matches("person.description", FieldValue::str("nice person"))
                 ])),
-                sort: Some(Sort::new("person.score").with_direction(SortDirection::ASC)),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("person.score").with_direction(SortDirection::ASC)],
                 scope: Some(DeltaScope {
                     date: Date::from_calendar_date(2020, Month::January, 1).unwrap(),
                     branch: Some(1)
@@ -1376,4 +4656,222 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn creates_group_by_with_aggregations() {
+        // given
+        let input = r#"
+            FROM players
+                WHERE active = true
+                GROUP BY sport
+                AGG avg(score), MAX(birth_date), count(*)
+        "#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Query {
+                explain: false,
+                entity: "players".to_string(),
+                projection: None,
+                group_by: Some("sport".to_string()),
+                aggregations: vec![
+                    Aggregation::new(AggregateFunction::Avg, "score"),
+                    Aggregation::new(AggregateFunction::Max, "birth_date"),
+                    Aggregation::count_all(),
+                ],
+                filter: Some(CompositeFilter::eq("active", FieldValue::Bool(true))),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: Vec::new(),
+                scope: None,
+                pagination: Pagination::default()
+            }
+        )
+    }
+
+    #[test]
+    fn rejects_aggregation_of_all_items_other_than_count() {
+        // given
+        let input = "FROM players AGG sum(*)";
+
+        // when
+        let result = QueryParser::parse_query(input);
+
+        // then
+        assert_eq!(
+            result,
+            Err(ParseError::InvalidQuery {
+                message: "only count can be applied to all the items in AGG statement",
+                span: Some(ErrorSpan {
+                    start: ErrorPosition {
+                        line: 1,
+                        column: 22
+                    },
+                    end: ErrorPosition {
+                        line: 1,
+                        column: 23
+                    }
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn parses_escaped_string_values() {
+        // given
+        let input = r#"FROM person WHERE person.name = "David \"The Rock\" \\ é""#;
+
+        // when
+        let result = QueryParser::parse_query(input).unwrap();
+
+        // then
+        assert_eq!(
+            result.filter,
+            Some(CompositeFilter::eq(
+                "person.name",
+                FieldValue::str("David \"The Rock\" \\ é")
+            ))
+        );
+    }
+
+    #[test]
+    fn prints_query_as_text() {
+        // given
+        let input = r#"SELECT name, score FROM players WHERE (sport = "Football" OR score BETWEEN 5 AND 9.5) AND NOT name IN ["David", "Say \"hi\""] AND active != true GROUP BY sport AGG avg(score), count(*) BRANCH 1 AS OF "2020-01-01" ORDER BY score DESC NULLS FIRST, name ASC OFFSET 10 LIMIT 20"#;
+        let query = Query::from_str(input).unwrap();
+
+        // when
+        let text = query.to_string();
+
+        // then
+        assert_eq!(
+            text,
+            r#"SELECT name, score FROM players WHERE (sport = "Football" OR score BETWEEN 5 AND 9.5) AND NOT name IN ["David", "Say \"hi\""] AND NOT active = true GROUP BY sport AGG avg(score), count(*) BRANCH 1 AS OF "2020-01-01" ORDER BY score DESC NULLS FIRST, name ASC OFFSET 10 LIMIT 20"#
+        );
+        assert_eq!(Query::from_str(&text).unwrap(), query);
+    }
+
+    #[test]
+    fn deserializes_query_from_json() {
+        // given
+        let input = r#"{
+            "entity": "players",
+            "filter": {
+                "and": [
+                    { "single": { "name": "score", "operation": { "between": [5.0, 9.5] } } },
+                    { "not": { "single": { "name": "name", "operation": { "contains": "David" } } } }
+                ]
+            },
+            "sort": [{ "by": "score", "direction": "desc" }],
+            "pagination": { "size": 10 },
+            "scope": { "branch": 1, "date": "2020-01-01" }
+        }"#;
+
+        // when
+        let query: Query = serde_json::from_str(input).unwrap();
+
+        // then
+        assert_eq!(
+            query,
+            Query {
+                explain: false,
+                entity: "players".to_string(),
+                projection: None,
+                group_by: None,
+                aggregations: Vec::new(),
+                filter: Some(CompositeFilter::and(vec![
+                    CompositeFilter::between("score", FieldValue::dec(5.0), FieldValue::dec(9.5)),
+                    CompositeFilter::negate(CompositeFilter::contains(
+                        "name",
+                        FieldValue::str("David")
+                    )),
+                ])),
+                boosts: Vec::new(),
+                highlight: Vec::new(),
+                sort: vec![Sort::new("score").with_direction(SortDirection::DESC)],
+                scope: Some(DeltaScope {
+                    date: Date::from_calendar_date(2020, Month::January, 1).unwrap(),
+                    branch: Some(1)
+                }),
+                pagination: Pagination::new(DEFAULT_START_PAGE, 10)
+            }
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"FROM players WHERE score BETWEEN 5.0 AND 9.5 AND NOT name CONTAINS "David" BRANCH 1 AS OF "2020-01-01" ORDER BY score DESC LIMIT 10"#
+        );
+    }
+
+    #[test]
+    fn serializes_query_values_to_json_and_back() {
+        // given
+        let input = r#"{"entity":"players","filter":{"and":[{"single":{"name":"id","operation":{"in":[1,2]}}},{"single":{"name":"score","operation":{"greaterOrEqual":5.5}}},{"single":{"name":"name","operation":{"fuzzy":{"value":"mesi","maxEdits":1}}}},{"not":{"single":{"name":"active","operation":{"eq":false}}}},{"single":{"name":"sport","operation":"isNull"}}]},"sort":[{"by":"score","direction":"desc","nulls":"first"}],"pagination":{"start":0,"size":10}}"#;
+
+        // when
+        let query: Query = serde_json::from_str(input).unwrap();
+
+        // then
+        assert_eq!(
+            query.filter,
+            Some(CompositeFilter::and(vec![
+                CompositeFilter::in_values(
+                    "id",
+                    vec![FieldValue::Integer(1), FieldValue::Integer(2)]
+                ),
+                CompositeFilter::ge("score", FieldValue::dec(5.5)),
+                CompositeFilter::fuzzy("name", FieldValue::str("mesi"), 1),
+                CompositeFilter::negate(CompositeFilter::eq("active", FieldValue::Bool(false))),
+                CompositeFilter::is_null("sport"),
+            ]))
+        );
+        assert_eq!(serde_json::to_string(&query).unwrap(), input);
+    }
+
+    #[test]
+    fn fails_to_deserialize_query_with_map_value() {
+        // given
+        let input = r#"{"entity":"players","filter":{"single":{"name":"score","operation":{"eq":{"Decimal":5.0}}}}}"#;
+
+        // when
+        let query = serde_json::from_str::<Query>(input);
+
+        // then
+        assert!(query.is_err());
+    }
+
+    #[test]
+    fn serializes_query_to_json_and_back() {
+        // given
+        let query = Query::from_str(
+            r#"FROM players WHERE sport IN ["Football"] GROUP BY active AGG max(birth_date) ORDER BY name"#,
+        )
+        .unwrap();
+
+        // when
+        let json = serde_json::to_string(&query).unwrap();
+
+        // then
+        assert_eq!(serde_json::from_str::<Query>(&json).unwrap(), query);
+    }
+
+    #[test]
+    fn prints_query_from_json_and_parses_it_back() {
+        // given
+        let input = r#"{"entity":"players","filter":{"and":[{"single":{"name":"score","operation":{"between":[5,9.5]}}},{"single":{"name":"titles","operation":{"in":[1,2.0]}}},{"single":{"name":"age","operation":{"greaterThan":-3}}}]}}"#;
+        let query: Query = serde_json::from_str(input).unwrap();
+
+        // when
+        let text = query.to_string();
+
+        // then
+        assert_eq!(
+            text,
+            "FROM players WHERE score BETWEEN 5 AND 9.5 AND titles IN [1, 2.0] AND age > -3.0"
+        );
+        assert_eq!(Query::from_str(&text).unwrap(), query);
+    }
 }
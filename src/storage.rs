@@ -1,17 +1,19 @@
 use std::borrow::Cow;
+use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 use std::convert::{TryFrom, TryInto};
 use std::path::Path;
 
 use heed::byteorder::BigEndian;
 use heed::{types::*, BoxedError, BytesDecode, BytesEncode};
-use heed::{Database, Env, EnvOpenOptions, RoTxn};
+use heed::{Database, Env, EnvOpenOptions, RoTxn, RwTxn};
 use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::analysis::SynonymSet;
 use crate::data::{date_to_timestamp, DataItem};
-use crate::index::{Index, IndexError, TypeDescriptor};
+use crate::index::{legacy, Index, IndexError, TypeDescriptor};
 use crate::query::{DeltaChange, DeltaScope};
 use crate::DataItemId;
 
@@ -20,6 +22,15 @@ const DATA_DB_NAME: &str = "data";
 const INDICES_DB_NAME: &str = "indices";
 const DOCUMENTS_DB_NAME: &str = "documents";
 const DELTAS_DB_NAME: &str = "deltas";
+const SYNONYMS_DB_NAME: &str = "synonyms";
+const METADATA_DB_NAME: &str = "metadata";
+
+const INDICES_VERSION_KEY: &str = "indices_version";
+
+/// Version of the layout of the stored indices. It must be increased whenever the layout
+/// changes, so that the indices stored with a previous layout are migrated when opening
+/// the storage. Storages without a version hold the layout of `legacy` indices.
+const INDICES_VERSION: u32 = 1;
 
 const ALL_ITEMS_KEY: &str = "__all";
 
@@ -84,6 +95,26 @@ pub struct StoredDelta {
     after: Index,
 }
 
+/// A stored delta holding `legacy` indices, only read to migrate it.
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacyStoredDelta {
+    affected: RoaringBitmap,
+    field_name: String,
+    before: legacy::Index,
+    after: legacy::Index,
+}
+
+impl From<LegacyStoredDelta> for StoredDelta {
+    fn from(delta: LegacyStoredDelta) -> Self {
+        StoredDelta {
+            affected: delta.affected,
+            field_name: delta.field_name,
+            before: delta.before.into(),
+            after: delta.after.into(),
+        }
+    }
+}
+
 impl StoredDelta {
     fn from_type(field_name: String, descriptor: &TypeDescriptor) -> Self {
         StoredDelta {
@@ -96,6 +127,7 @@ impl StoredDelta {
 }
 
 type BEU64 = U64<BigEndian>;
+type BEU32 = U32<BigEndian>;
 
 #[derive(Debug, PartialEq)]
 struct DeltaKey {
@@ -192,6 +224,14 @@ pub struct EntityStorage {
     /// of deltas for each field.
     deltas: Database<DeltaKeyCodec, SerdeBincode<HashMap<String, StoredDelta>>>,
 
+    /// Database storing the synonym sets of the indices, where the key is the field
+    /// name and the value is the list of synonym sets.
+    synonyms: Database<Str, SerdeBincode<Vec<SynonymSet>>>,
+
+    /// Database storing properties of the storage itself, such as the version of the
+    /// layout of the stored indices.
+    metadata: Database<Str, BEU32>,
+
     /// An in-memory key-value map to store type descriptors for each index.
     /// This is propagated during initialization.
     index_descriptors: papaya::HashMap<String, TypeDescriptor>,
@@ -209,7 +249,7 @@ impl EntityStorage {
         let env = unsafe {
             EnvOpenOptions::new()
                 .map_size(MAX_STORAGE_SIZE)
-                .max_dbs(4)
+                .max_dbs(6)
                 .open(path)?
         };
 
@@ -231,6 +271,14 @@ impl EntityStorage {
             .create_database(&mut txn, Some(DELTAS_DB_NAME))
             .map_err(|_| StorageError::CreateDatabase(DELTAS_DB_NAME))?;
 
+        let synonyms = env
+            .create_database(&mut txn, Some(SYNONYMS_DB_NAME))
+            .map_err(|_| StorageError::CreateDatabase(SYNONYMS_DB_NAME))?;
+
+        let metadata = env
+            .create_database(&mut txn, Some(METADATA_DB_NAME))
+            .map_err(|_| StorageError::CreateDatabase(METADATA_DB_NAME))?;
+
         txn.commit()?;
 
         let mut storage = EntityStorage {
@@ -240,14 +288,79 @@ impl EntityStorage {
             documents,
             data,
             deltas,
+            synonyms,
+            metadata,
             index_descriptors: Default::default(),
         };
 
+        storage.migrate_indices()?;
         storage.propagate_indices()?;
 
         Ok(storage)
     }
 
+    /// Migrate the indices stored with a previous layout to the current one. The indices are
+    /// rebuilt from the stored data, while the indices of the deltas are converted, as they
+    /// can't be rebuilt.
+    fn migrate_indices(&self) -> Result<(), StorageError> {
+        let mut txn = self.env.write_txn()?;
+
+        let version = self
+            .metadata
+            .get(&txn, INDICES_VERSION_KEY)?
+            .unwrap_or_default();
+
+        if version == INDICES_VERSION {
+            return Ok(());
+        }
+
+        if version > INDICES_VERSION {
+            return Err(StorageError::UnsupportedIndicesVersion(version));
+        }
+
+        let legacy_indices = self
+            .indices
+            .remap_data_type::<SerdeBincode<legacy::Index>>();
+
+        let mut commands = Vec::new();
+        for entry in legacy_indices.iter(&txn)? {
+            let (name, index) = entry?;
+            commands.push(CreateFieldIndex {
+                name: name.to_string(),
+                descriptor: Index::from(index).create_descriptor(),
+            });
+        }
+
+        self.indices.clear(&mut txn)?;
+        self.populate_indices(&mut txn, &commands)?;
+
+        let legacy_deltas = self
+            .deltas
+            .remap_data_type::<SerdeBincode<HashMap<String, LegacyStoredDelta>>>();
+
+        let mut deltas = Vec::new();
+        for entry in legacy_deltas.iter(&txn)? {
+            let (key, legacy_deltas) = entry?;
+            let stored_deltas: HashMap<String, StoredDelta> = legacy_deltas
+                .into_iter()
+                .map(|(field, delta)| (field, delta.into()))
+                .collect();
+
+            deltas.push((key, stored_deltas));
+        }
+
+        for (key, stored_deltas) in deltas {
+            self.deltas.put(&mut txn, &key, &stored_deltas)?;
+        }
+
+        self.metadata
+            .put(&mut txn, INDICES_VERSION_KEY, &INDICES_VERSION)?;
+
+        txn.commit()?;
+
+        Ok(())
+    }
+
     /// Propagate the index data into other in-memory data used for faster access to certain
     /// properties and reduce deserialization overhead while running certain operations.
     fn propagate_indices(&mut self) -> Result<(), StorageError> {
@@ -286,6 +399,7 @@ impl EntityStorage {
         self.data.clear(&mut txn)?;
         self.indices.clear(&mut txn)?;
         self.documents.clear(&mut txn)?;
+        self.synonyms.clear(&mut txn)?;
         self.index_descriptors.pin().clear();
 
         txn.commit()?;
@@ -323,20 +437,22 @@ impl EntityStorage {
             // Update indices in memory with the item data to reduce (de)serialization overhead
             // if we update index one by one in the DB.
             for (index_name, index_descriptor) in self.index_descriptors.pin().iter() {
-                let Some(value) = item.fields.get(index_name).cloned() else {
-                    continue;
+                let index = match indices_to_store.entry(index_name.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let index = self
+                            .indices
+                            .get(&txn, index_name)?
+                            .unwrap_or_else(|| Index::from_type(index_descriptor));
+
+                        entry.insert(index)
+                    }
                 };
 
-                if let Some(index) = indices_to_store.get_mut(index_name) {
-                    index.put(value, position)?;
-                } else {
-                    let mut index = self
-                        .indices
-                        .get(&txn, index_name)?
-                        .unwrap_or_else(|| Index::from_type(index_descriptor));
-
-                    index.put(value, position)?;
-                    indices_to_store.insert(index_name.clone(), index);
+                // Items without a value for the field are tracked as missing in the index
+                match item.fields.get(index_name).cloned() {
+                    Some(value) => index.put(value, position)?,
+                    None => index.put_missing(position),
                 }
             }
 
@@ -365,46 +481,57 @@ impl EntityStorage {
     pub fn create_indices(&self, commands: Vec<CreateFieldIndex>) -> Result<(), StorageError> {
         let mut txn = self.env.write_txn()?;
 
+        self.populate_indices(&mut txn, &commands)?;
+
+        txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Populate the indices defined by the provided commands with the stored data, using
+    /// the current transaction.
+    fn populate_indices(
+        &self,
+        txn: &mut RwTxn,
+        commands: &[CreateFieldIndex],
+    ) -> Result<(), StorageError> {
         let mut indices_to_store: HashMap<&String, Index> = HashMap::new();
 
-        let entries = self.data.iter(&txn)?;
+        // Create the new indices and append them in memory, after they are populated with the
+        // items' data they will be stored.
+        for command in commands {
+            let index = self
+                .indices
+                .get(txn, &command.name)?
+                .unwrap_or_else(|| Index::from_type(&command.descriptor));
+
+            self.index_descriptors
+                .pin()
+                .insert(command.name.clone(), command.descriptor.clone());
+
+            indices_to_store.insert(&command.name, index);
+        }
+
+        let entries = self.data.iter(txn)?;
 
         // Iterate over each item and populate the data to the new indices
         for entry in entries {
             let (id, item) = entry?;
-            for command in &commands {
-                let Some(value) = item.fields.get(&command.name).cloned() else {
-                    continue;
-                };
+            let position = id_to_position(id);
 
-                let position = id_to_position(id);
-
-                if let Some(index) = indices_to_store.get_mut(&command.name) {
-                    index.put(value, position)?;
-                } else {
-                    // Create the new index and appended in memory, after it's populated
-                    // with the item's data it will be stored.
-                    let mut index = self
-                        .indices
-                        .get(&txn, &command.name)?
-                        .unwrap_or_else(|| Index::from_type(&command.descriptor));
-
-                    index.put(value, position)?;
-                    indices_to_store.insert(&command.name, index);
-                    self.index_descriptors
-                        .pin()
-                        .insert(command.name.clone(), command.descriptor.clone());
+            for (name, index) in indices_to_store.iter_mut() {
+                match item.fields.get(*name).cloned() {
+                    Some(value) => index.put(value, position)?,
+                    None => index.put_missing(position),
                 }
             }
         }
 
         // Update the stored indices with the new entries
         for (name, index) in indices_to_store {
-            self.indices.put(&mut txn, name, &index)?;
+            self.indices.put(txn, name, &index)?;
         }
 
-        txn.commit()?;
-
         Ok(())
     }
 
@@ -459,7 +586,8 @@ impl EntityStorage {
         let mut field_indices = BTreeMap::new();
 
         for field in fields {
-            if let Some(index) = self.indices.get(txn, field)? {
+            if let Some(mut index) = self.indices.get(txn, field)? {
+                self.apply_synonyms(txn, field, &mut index)?;
                 field_indices.insert(field.to_string(), index);
             }
         }
@@ -480,7 +608,8 @@ impl EntityStorage {
         let mut field_indices = BTreeMap::new();
 
         for item in self.indices.iter(txn)? {
-            let (field, index) = item?;
+            let (field, mut index) = item?;
+            self.apply_synonyms(txn, field, &mut index)?;
             field_indices.insert(field.to_string(), index);
         }
 
@@ -493,6 +622,56 @@ impl EntityStorage {
         })
     }
 
+    /// Set the stored synonyms of a field, if any, in its index.
+    fn apply_synonyms(
+        &self,
+        txn: &RoTxn,
+        field: &str,
+        index: &mut Index,
+    ) -> Result<(), StorageError> {
+        if let Some(sets) = self.synonyms.get(txn, field)? {
+            index.set_synonyms(&sets)?;
+        }
+
+        Ok(())
+    }
+
+    /// Store the synonym sets of a field, replacing any previous ones. The field must
+    /// have a term index, and no synonyms are kept if the provided sets are empty.
+    pub fn store_synonyms(&self, field: &str, sets: Vec<SynonymSet>) -> Result<(), StorageError> {
+        let mut txn = self.env.write_txn()?;
+
+        // Check that the synonyms can be used by the field's index before storing them
+        let mut index = self
+            .indices
+            .get(&txn, field)?
+            .ok_or_else(|| StorageError::MissingIndex(field.to_string()))?;
+        index.set_synonyms(&sets)?;
+
+        if sets.is_empty() {
+            self.synonyms.delete(&mut txn, field)?;
+        } else {
+            self.synonyms.put(&mut txn, field, &sets)?;
+        }
+
+        txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Read the stored synonym sets of every field.
+    pub fn read_synonyms(&self) -> Result<BTreeMap<String, Vec<SynonymSet>>, StorageError> {
+        let txn = self.env.read_txn()?;
+
+        let mut synonyms = BTreeMap::new();
+        for entry in self.synonyms.iter(&txn)? {
+            let (field, sets) = entry?;
+            synonyms.insert(field.to_string(), sets);
+        }
+
+        Ok(synonyms)
+    }
+
     fn read_deltas(
         &self,
         txn: &RoTxn,
@@ -588,6 +767,18 @@ impl EntityStorage {
         Ok(indices.with_affected(affected))
     }
 
+    /// Names of the fields indexed with a term index.
+    pub(crate) fn term_fields(&self) -> Vec<String> {
+        self.index_descriptors
+            .pin()
+            .iter()
+            .filter(|(_, descriptor)| {
+                matches!(descriptor, TypeDescriptor::String(descriptor) if descriptor.term)
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
     /// Read indices for a given set of fields. In case a field is not found, it won't be present
     /// in the returned `EntityIndices`.
     pub fn read_current_indices(&self, fields: &[String]) -> Result<EntityIndices, StorageError> {
@@ -681,8 +872,11 @@ impl EntityStorage {
             for delta in deltas {
                 let position = id_to_position(delta.id);
 
-                if let Some(before) = index.as_ref().and_then(|index| index.get_value(position)) {
-                    stored_delta.before.put(before, position)?;
+                // Keep track of the items without a value before the delta, so that they are no
+                // longer missing once the delta is applied.
+                match index.as_ref().and_then(|index| index.get_value(position)) {
+                    Some(before) => stored_delta.before.put(before, position)?,
+                    None => stored_delta.before.put_missing(position),
                 }
 
                 stored_delta.after.put(delta.after.clone(), position)?;
@@ -709,8 +903,12 @@ pub enum StorageError {
     CreateStoragePath(#[from] std::io::Error),
     #[error(transparent)]
     DbOperation(#[from] heed::Error),
+    #[error("index is not present for field `{0}`")]
+    MissingIndex(String),
     #[error(transparent)]
     Index(#[from] IndexError),
+    #[error("indices are stored with an unsupported version `{0}`")]
+    UnsupportedIndicesVersion(u32),
 }
 
 #[derive(Default, Debug)]
@@ -745,11 +943,21 @@ pub struct CreateFieldIndex {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use heed::types::SerdeBincode;
     use heed::{BytesDecode, BytesEncode};
     use lazy_static::lazy_static;
+    use ordered_float::OrderedFloat;
+    use roaring::RoaringBitmap;
     use time::{Date, Month};
 
-    use super::{date_to_timestamp, DeltaKey, DeltaKeyCodec};
+    use super::{
+        date_to_timestamp, legacy, DeltaKey, DeltaKeyCodec, EntityStorage, LegacyStoredDelta,
+        INDICES_VERSION_KEY,
+    };
+    use crate::data::{DataItem, FieldValue};
+    use crate::index::{Index, TypeDescriptor};
 
     lazy_static! {
         static ref DATE: Date = Date::from_calendar_date(2023, Month::January, 1).unwrap();
@@ -767,4 +975,96 @@ mod tests {
         // then
         assert_eq!(key, decoded);
     }
+
+    #[test]
+    fn migrates_legacy_indices() {
+        // given
+        let name = "test_legacy_indices";
+        let storage = EntityStorage::init(name).unwrap();
+        storage
+            .carry(&[
+                DataItem::new(
+                    0,
+                    BTreeMap::from([
+                        ("name".to_string(), FieldValue::str("Michael Jordan")),
+                        ("score".to_string(), FieldValue::Integer(10)),
+                    ]),
+                ),
+                DataItem::new(
+                    1,
+                    BTreeMap::from([("name".to_string(), FieldValue::str("Lionel Messi"))]),
+                ),
+            ])
+            .unwrap();
+
+        let legacy_score = || {
+            legacy::Index::Numeric(legacy::NumericIndex {
+                inner: legacy::SortableIndex(BTreeMap::from([(
+                    OrderedFloat(10.0),
+                    RoaringBitmap::from([0]),
+                )])),
+            })
+        };
+
+        // Store the indices and deltas as encoded before their layout was versioned
+        let mut txn = storage.env.write_txn().unwrap();
+        storage
+            .indices
+            .remap_data_type::<SerdeBincode<legacy::Index>>()
+            .put(&mut txn, "score", &legacy_score())
+            .unwrap();
+
+        let key = DeltaKey::new(None, date_to_timestamp(*DATE));
+        storage
+            .deltas
+            .remap_data_type::<SerdeBincode<HashMap<String, LegacyStoredDelta>>>()
+            .put(
+                &mut txn,
+                &key,
+                &HashMap::from([(
+                    "score".to_string(),
+                    LegacyStoredDelta {
+                        affected: RoaringBitmap::from([0]),
+                        field_name: "score".to_string(),
+                        before: legacy_score(),
+                        after: legacy_score(),
+                    },
+                )]),
+            )
+            .unwrap();
+
+        storage
+            .metadata
+            .delete(&mut txn, INDICES_VERSION_KEY)
+            .unwrap();
+        txn.commit().unwrap();
+        drop(storage);
+
+        // when
+        let storage = EntityStorage::init(name).unwrap();
+
+        // then
+        let mut expected = Index::from_type(&TypeDescriptor::Numeric);
+        expected.put(FieldValue::Integer(10), 0).unwrap();
+        expected.put_missing(1);
+
+        let indices = storage
+            .read_current_indices(&["score".to_string()])
+            .unwrap();
+        let score = indices.field_indices.get("score").unwrap();
+        assert_eq!(score, &expected);
+        assert_eq!(score.get_value(0), Some(FieldValue::Integer(10)));
+        assert_eq!(score.get_value(1), None);
+
+        let txn = storage.env.read_txn().unwrap();
+        let deltas = storage.deltas.get(&txn, &key).unwrap().unwrap();
+        assert_eq!(deltas["score"].after, Index::from(legacy_score()));
+        assert_eq!(
+            deltas["score"].after.get_value(0),
+            Some(FieldValue::Integer(10))
+        );
+        drop(txn);
+
+        std::fs::remove_dir_all(storage.get_path()).unwrap();
+    }
 }
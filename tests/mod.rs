@@ -28,6 +28,15 @@ mod integration_tests {
         // executes term query
         executes_term_query(entity_name).await;
 
+        // rejects invalid query
+        rejects_invalid_query(entity_name).await;
+
+        // executes query from JSON
+        executes_json_query(entity_name).await;
+
+        // gets options from JSON
+        reads_filter_options_from_json(entity_name).await;
+
         // adds deltas
         adds_deltas(entity_name).await;
 
@@ -200,7 +209,8 @@ mod integration_tests {
                             "Cristiano Ronaldo": 1,
                             "Lionel Messi": 1,
                             "Michael Jordan": 1
-                        }
+                        },
+                        "missing": 0
                     },
                     {
                         "field": "score",
@@ -208,7 +218,8 @@ mod integration_tests {
                             "8.7": 1,
                             "9": 1,
                             "9.5": 1
-                        }
+                        },
+                        "missing": 0
                     }
                 ]"#
             )
@@ -271,12 +282,160 @@ mod integration_tests {
                                 "sport": "Football"
                             }
                         }
+                    ],
+                    "total": 3,
+                    "start": 0,
+                    "size": 3,
+                    "hasMore": false
+                }"#
+            )
+        );
+    }
+
+    async fn rejects_invalid_query(name: &str) {
+        // given
+        let payload = format!(
+            r#"{{
+                "query": "FROM {name} WHERE score >"
+            }}"#
+        );
+
+        // when
+        let response = Client::new()
+            .post("http://127.0.0.1:3000/search")
+            .header("Content-Type", "application/json")
+            .body(payload)
+            .send()
+            .await
+            .unwrap();
+
+        // then
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: serde_json::Value = response.json().await.unwrap();
+        let column = format!("FROM {name} WHERE score >").len() + 1;
+
+        assert_eq!(
+            response_body,
+            serde_json::json!({
+                "code": "syntax_error",
+                "message": format!("unexpected input at line 1, column {column}, expected one of: value"),
+                "span": {
+                    "start": { "line": 1, "column": column },
+                    "end": { "line": 1, "column": column }
+                },
+                "expected": ["value"],
+                "pointer": format!(
+                    "unexpected input at line 1, column {column}, expected one of: value\n  |\n1 | FROM {name} WHERE score >\n  | {}^",
+                    " ".repeat(column - 1)
+                )
+            })
+        );
+    }
+
+    async fn executes_json_query(name: &str) {
+        // given
+        let payload = format!(
+            r#"{{
+                "entity": "{name}",
+                "filter": {{
+                    "and": [
+                        {{ "single": {{ "name": "score", "operation": {{ "greaterThan": 2 }} }} }},
+                        {{ "not": {{ "single": {{ "name": "name", "operation": {{ "contains": "Cristiano" }} }} }} }}
                     ]
+                }},
+                "sort": [{{ "by": "score", "direction": "desc" }}],
+                "pagination": {{ "size": 1 }}
+            }}"#
+        );
+
+        // when
+        let response = Client::new()
+            .post("http://127.0.0.1:3000/search/json")
+            .header("Content-Type", "application/json")
+            .body(payload)
+            .send()
+            .await
+            .unwrap();
+
+        // then
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body = response.text().await.unwrap();
+        assert_eq!(
+            normalize(&response_body),
+            normalize(
+                r#"{
+                    "data": [
+                        {
+                            "id": 1,
+                            "fields": {
+                                "active": true,
+                                "birth_date": "1987-06-24",
+                                "name": "Lionel Messi",
+                                "score": 9.5,
+                                "sport": "Football"
+                            }
+                        }
+                    ],
+                    "total": 2,
+                    "start": 0,
+                    "size": 1,
+                    "hasMore": true
                 }"#
             )
         );
     }
 
+    async fn reads_filter_options_from_json(name: &str) {
+        // given
+        let payload = format!(
+            r#"{{
+                "entity": "{name}",
+                "filter": {{ "single": {{ "name": "score", "operation": {{ "lessThan": 9 }} }} }}
+            }}"#
+        );
+
+        // when
+        let response = Client::new()
+            .post("http://127.0.0.1:3000/options/json")
+            .header("Content-Type", "application/json")
+            .body(payload)
+            .send()
+            .await
+            .unwrap();
+
+        // then
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body = response.text().await.unwrap();
+        assert_eq!(
+            normalize(&response_body),
+            normalize(
+                r#"[
+                    {
+                        "field": "name",
+                        "values":{
+                            "Cristiano Ronaldo": 1,
+                            "Lionel Messi": 0,
+                            "Michael Jordan": 0
+                        },
+                        "missing": 0
+                    },
+                    {
+                        "field": "score",
+                        "values": {
+                            "8.7": 1,
+                            "9": 0,
+                            "9.5": 0
+                        },
+                        "missing": 0
+                    }
+                ]"#
+            )
+        );
+    }
+
     async fn executes_term_query(name: &str) {
         // given
         let payload = format!(
@@ -311,7 +470,8 @@ mod integration_tests {
                                 "name": "Lionel Messi",
                                 "score": 9.5,
                                 "sport": "Football"
-                            }
+                            },
+                            "score": 0.9808292530117263
                         },
                         {
                             "id": 0,
@@ -321,9 +481,14 @@ mod integration_tests {
                                 "name": "Michael Jordan",
                                 "score": 9,
                                 "sport": "Basketball"
-                            }
+                            },
+                            "score": 1.9616585060234526
                         }
-                    ]
+                    ],
+                    "total": 2,
+                    "start": 0,
+                    "size": 2,
+                    "hasMore": false
                 }"#
             )
         );
@@ -397,7 +562,8 @@ mod integration_tests {
                                 "name": "Michael Jeffrey Jordan",
                                 "score": 9.0,
                                 "sport": "Basketball"
-                            }
+                            },
+                            "score": 0.8781843311849177
                         },
                         {
                             "id": 1,
@@ -407,9 +573,14 @@ mod integration_tests {
                                 "name": "Lionel Messi",
                                 "score": 6.0,
                                 "sport": "Football"
-                            }
+                            },
+                            "score": 0.0
                         }
-                    ]
+                    ],
+                    "total": 2,
+                    "start": 0,
+                    "size": 2,
+                    "hasMore": false
                 }"#
             )
         );
@@ -445,7 +616,8 @@ mod integration_tests {
                             "Cristiano Ronaldo": 0,
                             "Lionel Messi": 1,
                             "Michael Jeffrey Jordan": 0
-                        }
+                        },
+                        "missing": 0
                     },
                     {
                         "field": "score",
@@ -453,7 +625,8 @@ mod integration_tests {
                             "6": 1,
                             "8.7": 0,
                             "9": 0
-                        }
+                        },
+                        "missing": 0
                     }
                 ]"#
             )
@@ -552,7 +725,11 @@ mod integration_tests {
                                 "sport": "Football"
                             }
                         }
-                    ]
+                    ],
+                    "total": 1,
+                    "start": 0,
+                    "size": 1,
+                    "hasMore": false
                 }"#
             )
         );
@@ -592,7 +769,11 @@ mod integration_tests {
                                 "sport": "Basketball"
                             }
                         }
-                    ]
+                    ],
+                    "total": 1,
+                    "start": 0,
+                    "size": 1,
+                    "hasMore": false
                 }"#
             )
         );
@@ -628,7 +809,8 @@ mod integration_tests {
                             "5": 1,
                             "8.7": 0,
                             "9.5": 0
-                        }
+                        },
+                        "missing": 0
                     }
                 ]"#
             )